@@ -1,12 +1,32 @@
-from gg_core._core import run, parse_config, GameState, Action, AgentConfig, CameraConfig, EntityType, GGConfig
+from gg_core._core import run, open_viewer, parse_config, render_episode, rollout, mcts, train_tabular, visitation_counts, load_scenario, scenario_names, GameState, Action, AgentConfig, CameraConfig, CameraMode, EntityType, GGConfig, Renderer, ConfigError, InvalidConfigError, BoardGenerationError, PolicyShapeError, PolicyLayout, ActionStats, TdAlgorithm, Schedule, Scenario, Viewer
 
 __all__ = [
     "run",
+    "open_viewer",
     "parse_config",
+    "render_episode",
+    "rollout",
+    "mcts",
+    "train_tabular",
+    "visitation_counts",
+    "load_scenario",
+    "scenario_names",
     "GameState",
     "GGConfig",
     "Action",
     "AgentConfig",
     "CameraConfig",
+    "CameraMode",
     "EntityType",
+    "Renderer",
+    "ConfigError",
+    "InvalidConfigError",
+    "BoardGenerationError",
+    "PolicyShapeError",
+    "PolicyLayout",
+    "ActionStats",
+    "TdAlgorithm",
+    "Schedule",
+    "Scenario",
+    "Viewer",
 ]
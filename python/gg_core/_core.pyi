@@ -2,9 +2,33 @@
 # ruff: noqa: E501, F401
 
 import builtins
+import numpy
+import numpy.typing
 import typing
 from enum import Enum
 
+class ActionStats:
+    r"""
+    Visit and value statistics of one action at the root of an MCTS search.
+    """
+    @property
+    def action(self) -> Action: ...
+    @property
+    def visits(self) -> builtins.int: ...
+    @property
+    def value(self) -> builtins.float:
+        r"""
+        Mean discounted return of the simulations that started with this action.
+        """
+    def to_json(self) -> builtins.str:
+        r"""
+        Serializes the action with its visit count and mean value to JSON.
+        """
+    @staticmethod
+    def from_json(json:builtins.str) -> ActionStats: ...
+    def __reduce__(self) -> tuple[typing.Any, typing.Any]: ...
+    def __repr__(self) -> builtins.str: ...
+
 class AgentConfig:
     @property
     def name(self) -> builtins.str: ...
@@ -18,6 +42,17 @@ class AgentConfig:
     def transition(self) -> builtins.list[builtins.float]: ...
     @transition.setter
     def transition(self, value: builtins.list[builtins.float]) -> None: ...
+    def to_json(self) -> builtins.str:
+        r"""
+        Serializes the agent's name, ghost policy and transition model to JSON.
+        """
+    @staticmethod
+    def from_json(json:builtins.str) -> AgentConfig: ...
+    def __reduce__(self) -> tuple[typing.Any, typing.Any]: ...
+    def __getstate__(self) -> builtins.str: ...
+    def __setstate__(self, state:builtins.str) -> None: ...
+    def __repr__(self) -> builtins.str: ...
+    def __str__(self) -> builtins.str: ...
 
 class Board:
     @property
@@ -33,12 +68,149 @@ class Board:
     @property
     def height(self) -> builtins.int: ...
     def __getitem__(self, position:tuple[builtins.int, builtins.int]) -> EntityType: ...
+    def is_free(self, position:tuple[builtins.int, builtins.int]) -> builtins.bool:
+        r"""
+        Whether the agent may stand on `position`: it is on the board and not a wall.
+        """
+    def free_cells(self) -> builtins.list[tuple[builtins.int, builtins.int]]:
+        r"""
+        Cells that are not walls, in row-major order.
+        """
+    def neighbors(self, position:tuple[builtins.int, builtins.int]) -> builtins.list[tuple[builtins.int, builtins.int]]:
+        r"""
+        Free cells the agent can reach from `position` in one move, in action tag order. Moves
+        into a wall or off the edge of the board leave the agent in place and are not listed.
+        """
+    def distance(self, start:tuple[builtins.int, builtins.int], goal:tuple[builtins.int, builtins.int]) -> typing.Optional[builtins.int]:
+        r"""
+        Number of moves the agent needs to get from `start` to `goal` around the walls, or
+        `None` when `goal` cannot be reached.
+        """
+    def shortest_path(self, start:tuple[builtins.int, builtins.int], goal:tuple[builtins.int, builtins.int]) -> typing.Optional[builtins.list[tuple[builtins.int, builtins.int]]]:
+        r"""
+        A shortest sequence of free cells from `start` to `goal`, both included, found by
+        breadth-first search. `None` when either end is a wall or `goal` cannot be reached.
+        """
+    def to_json(self) -> builtins.str:
+        r"""
+        Serializes the board's size, walls and occupants to JSON.
+        """
+    @staticmethod
+    def from_json(json:builtins.str) -> Board: ...
+    def __reduce__(self) -> tuple[typing.Any, typing.Any]: ...
+    def __getstate__(self) -> builtins.str: ...
+    def __setstate__(self, state:builtins.str) -> None: ...
+
+class BoardGenerationError(builtins.RuntimeError):
+    r"""
+    Raised when a board cannot be generated from an otherwise valid config.
+    """
+    ...
 
 class CameraConfig:
     @property
     def scale(self) -> builtins.float: ...
     @scale.setter
     def scale(self, value: builtins.float) -> None: ...
+    @property
+    def mode(self) -> CameraMode: ...
+    @mode.setter
+    def mode(self, value: CameraMode) -> None: ...
+    @property
+    def pan_speed(self) -> builtins.float:
+        r"""
+        World units panned per second while an arrow key is held.
+        """
+    @pan_speed.setter
+    def pan_speed(self, value: builtins.float) -> None:
+        r"""
+        World units panned per second while an arrow key is held.
+        """
+    @property
+    def zoom_speed(self) -> builtins.float:
+        r"""
+        Zoom rate while `+` or `-` is held: the view shrinks by a factor of `e` every
+        `1 / zoom_speed` seconds. One line of the scroll wheel zooms as much as a tenth of a
+        second of holding a key.
+        """
+    @zoom_speed.setter
+    def zoom_speed(self, value: builtins.float) -> None:
+        r"""
+        Zoom rate while `+` or `-` is held: the view shrinks by a factor of `e` every
+        `1 / zoom_speed` seconds. One line of the scroll wheel zooms as much as a tenth of a
+        second of holding a key.
+        """
+    @property
+    def orbit_sensitivity(self) -> builtins.float:
+        r"""
+        Radians an orbit camera turns per pixel of mouse drag.
+        """
+    @orbit_sensitivity.setter
+    def orbit_sensitivity(self, value: builtins.float) -> None:
+        r"""
+        Radians an orbit camera turns per pixel of mouse drag.
+        """
+    @property
+    def fov_degrees(self) -> builtins.float:
+        r"""
+        Vertical field of view of an orbit camera.
+        """
+    @fov_degrees.setter
+    def fov_degrees(self, value: builtins.float) -> None:
+        r"""
+        Vertical field of view of an orbit camera.
+        """
+    @property
+    def pitch_degrees(self) -> builtins.float:
+        r"""
+        Starting angle of an orbit camera above the board; 90 looks straight down.
+        """
+    @pitch_degrees.setter
+    def pitch_degrees(self, value: builtins.float) -> None:
+        r"""
+        Starting angle of an orbit camera above the board; 90 looks straight down.
+        """
+    @property
+    def follow_agent(self) -> builtins.bool:
+        r"""
+        Whether the camera starts out keeping the agent in the middle of the view.
+        """
+    @follow_agent.setter
+    def follow_agent(self, value: builtins.bool) -> None:
+        r"""
+        Whether the camera starts out keeping the agent in the middle of the view.
+        """
+    def to_json(self) -> builtins.str:
+        r"""
+        Serializes the camera settings to JSON.
+        """
+    @staticmethod
+    def from_json(json:builtins.str) -> CameraConfig: ...
+    def __reduce__(self) -> tuple[typing.Any, typing.Any]: ...
+    def __getstate__(self) -> builtins.str: ...
+    def __setstate__(self, state:builtins.str) -> None: ...
+    def __repr__(self) -> builtins.str: ...
+    def __str__(self) -> builtins.str: ...
+
+class ConfigError:
+    r"""
+    A single violated invariant in a `GGConfig`, naming the offending field by its dotted path.
+    """
+    @property
+    def field(self) -> builtins.str: ...
+    @property
+    def message(self) -> builtins.str: ...
+    def to_json(self) -> builtins.str:
+        r"""
+        Serializes the field name and message to JSON.
+        """
+    @staticmethod
+    def from_json(json:builtins.str) -> ConfigError: ...
+    def __reduce__(self) -> tuple[typing.Any, typing.Any]: ...
+    def __getstate__(self) -> builtins.str: ...
+    def __setstate__(self, state:builtins.str) -> None: ...
+    def __repr__(self) -> builtins.str: ...
+    def __str__(self) -> builtins.str: ...
 
 class EntityType:
     class Empty(EntityType):
@@ -73,6 +245,13 @@ class EntityType:
         def __len__(self) -> builtins.int: ...
         def __getitem__(self, key:builtins.int) -> typing.Any: ...
     
+    def to_json(self) -> builtins.str:
+        r"""
+        Serializes this cell's entity to JSON.
+        """
+    @staticmethod
+    def from_json(json:builtins.str) -> EntityType: ...
+    def __reduce__(self) -> tuple[typing.Any, typing.Any]: ...
     ...
 
 class GGConfig:
@@ -97,6 +276,44 @@ class GGConfig:
     @render_delay_secs.setter
     def render_delay_secs(self, value: builtins.float) -> None: ...
     @property
+    def auto_reset_secs(self) -> typing.Optional[builtins.float]:
+        r"""
+        Seconds to show the end of an episode before the windowed app starts a new one, or
+        `None` to wait for a manual restart.
+        """
+    @auto_reset_secs.setter
+    def auto_reset_secs(self, value: typing.Optional[builtins.float]) -> None:
+        r"""
+        Seconds to show the end of an episode before the windowed app starts a new one, or
+        `None` to wait for a manual restart.
+        """
+    @property
+    def level(self) -> typing.Optional[builtins.str]:
+        r"""
+        Path of a level file, as saved by the board editor or `Board.to_json`. When set, the
+        board is read from it instead of being generated from `generation_seed`.
+        """
+    @level.setter
+    def level(self, value: typing.Optional[builtins.str]) -> None:
+        r"""
+        Path of a level file, as saved by the board editor or `Board.to_json`. When set, the
+        board is read from it instead of being generated from `generation_seed`.
+        """
+    @property
+    def scenario(self) -> typing.Optional[builtins.str]:
+        r"""
+        A scenario from the built-in registry, as `name@version` or a bare name for its latest
+        version. When set, the board is the scenario's fixed layout; `load_scenario` returns a
+        config with this and the rest of the scenario's settings filled in.
+        """
+    @scenario.setter
+    def scenario(self, value: typing.Optional[builtins.str]) -> None:
+        r"""
+        A scenario from the built-in registry, as `name@version` or a bare name for its latest
+        version. When set, the board is the scenario's fixed layout; `load_scenario` returns a
+        config with this and the rest of the scenario's settings filled in.
+        """
+    @property
     def generation_seed(self) -> typing.Optional[builtins.int]: ...
     @generation_seed.setter
     def generation_seed(self, value: typing.Optional[builtins.int]) -> None: ...
@@ -112,6 +329,26 @@ class GGConfig:
     def headless(self) -> builtins.bool: ...
     @headless.setter
     def headless(self, value: builtins.bool) -> None: ...
+    @property
+    def renderer(self) -> Renderer: ...
+    @renderer.setter
+    def renderer(self, value: Renderer) -> None: ...
+    def validate(self) -> builtins.list[ConfigError]:
+        r"""
+        Checks the invariants board generation and simulation rely on, returning one error per
+        violation. An empty list means the config is valid.
+        """
+    def to_json(self) -> builtins.str:
+        r"""
+        Serializes the whole config, sub-configs and seeds included, to JSON.
+        """
+    @staticmethod
+    def from_json(json:builtins.str) -> GGConfig: ...
+    def __reduce__(self) -> tuple[typing.Any, typing.Any]: ...
+    def __getstate__(self) -> builtins.str: ...
+    def __setstate__(self, state:builtins.str) -> None: ...
+    def __repr__(self) -> builtins.str: ...
+    def __str__(self) -> builtins.str: ...
 
 class GameState:
     @property
@@ -120,11 +357,75 @@ class GameState:
     def reward(self) -> builtins.int: ...
     @property
     def done(self) -> builtins.bool: ...
-    def all_states(self) -> builtins.list[GameState]: ...
+    @property
+    def executed_action(self) -> typing.Optional[Action]:
+        r"""
+        The direction the agent actually moved in on the last step, which differs from the
+        chosen action when it slipped. `None` before the first step.
+        """
+    def all_states(self, reachable_only:builtins.bool=False) -> builtins.list[GameState]:
+        r"""
+        Every state on this board in `state_index` order, including terminal ones. With
+        `reachable_only`, only the states reachable from the start of the episode under the
+        transition model are kept. States share this state's config, initial board and seeds.
+        """
     def next_state(self, action:Action) -> GameState: ...
     def with_seed(self, seed:builtins.int) -> GameState: ...
+    def get_rng_state(self) -> builtins.str:
+        r"""
+        Snapshot of this state's random stream, to be restored with `set_rng_state`. The
+        snapshot is opaque but can be stored or sent between processes.
+        """
+    def set_rng_state(self, state:builtins.str) -> None:
+        r"""
+        Rewinds or advances this state's random stream to a snapshot from `get_rng_state`, so
+        the following steps draw the same random numbers as they did after the snapshot.
+        """
+    def fork(self, seed_offset:builtins.int) -> GameState:
+        r"""
+        Returns a copy of this state with its own random stream, derived from the current
+        position of this state's stream and `seed_offset`. Forking twice with the same offset
+        gives the same stream, different offsets give independent ones, and this state's own
+        stream is left untouched.
+        """
     def step(self, action:Action) -> GameState: ...
     def reset(self) -> tuple[GameState, builtins.int]: ...
+    def successors(self) -> builtins.list[GameState]:
+        r"""
+        States that can follow this one with non-zero probability: the agent may slip in any
+        direction, then the ghost makes every move its policy allows. Terminal states have none.
+        """
+    def num_states(self) -> builtins.int:
+        r"""
+        Number of distinct states on this board: every non-wall cell for the agent, times every
+        cell for the ghost when there is one (the ghost flies over walls). Goblets stay put
+        during an episode, where landing on one ends it, so the only goblet status episodes
+        reach is every goblet of the initial board in place, and it adds no states of its own.
+        """
+    def state_index(self) -> builtins.int:
+        r"""
+        Index of this state in `0..num_states()`. The agent's rank among the non-wall cells in
+        row-major order is the major index and, with a ghost, the ghost cell `x + y * width` is
+        the minor index. States whose goblets differ from their initial board's cannot occur in
+        an episode and have no index. `from_index` is the inverse.
+        """
+    def from_index(self, index:builtins.int) -> GameState:
+        r"""
+        Returns the state with index `index` on this board, keeping this state's config, initial
+        board and random stream.
+        """
+    def to_json(self) -> builtins.str:
+        r"""
+        Serializes this state to JSON, its config, initial board and random stream included,
+        so a restored state continues the same episode.
+        """
+    @staticmethod
+    def from_json(json:builtins.str) -> GameState: ...
+    def __reduce__(self) -> tuple[typing.Any, typing.Any]: ...
+    def __getstate__(self) -> builtins.str: ...
+    def __setstate__(self, state:builtins.str) -> None: ...
+    def __hash__(self) -> builtins.int: ...
+    def __eq__(self, other:GameState) -> builtins.bool: ...
     def __repr__(self) -> builtins.str: ...
     def __str__(self) -> builtins.str: ...
 
@@ -133,6 +434,15 @@ class Goblet:
     def position(self) -> tuple[builtins.int, builtins.int]: ...
     @property
     def reward(self) -> builtins.int: ...
+    def to_json(self) -> builtins.str:
+        r"""
+        Serializes the goblet's position and reward to JSON.
+        """
+    @staticmethod
+    def from_json(json:builtins.str) -> Goblet: ...
+    def __reduce__(self) -> tuple[typing.Any, typing.Any]: ...
+    def __getstate__(self) -> builtins.str: ...
+    def __setstate__(self, state:builtins.str) -> None: ...
 
 class GobletConfig:
     @property
@@ -143,6 +453,127 @@ class GobletConfig:
     def max_reward(self) -> builtins.int: ...
     @max_reward.setter
     def max_reward(self, value: builtins.int) -> None: ...
+    def to_json(self) -> builtins.str:
+        r"""
+        Serializes the goblet count and reward bound to JSON.
+        """
+    @staticmethod
+    def from_json(json:builtins.str) -> GobletConfig: ...
+    def __reduce__(self) -> tuple[typing.Any, typing.Any]: ...
+    def __getstate__(self) -> builtins.str: ...
+    def __setstate__(self, state:builtins.str) -> None: ...
+    def __repr__(self) -> builtins.str: ...
+    def __str__(self) -> builtins.str: ...
+
+class InvalidConfigError(builtins.ValueError):
+    r"""
+    Raised when a GGConfig violates one of the invariants checked by GGConfig.validate(), or when a level or board does not fit the config it is played with.
+    """
+    ...
+
+class PolicyShapeError(builtins.ValueError):
+    r"""
+    Raised when a policy does not match the shape of the board it is run on.
+    """
+    ...
+
+class Schedule:
+    r"""
+    A value that changes from episode to episode, such as epsilon or the learning rate.
+    """
+    class Constant(Schedule):
+        __match_args__ = ("value",)
+        @property
+        def value(self) -> builtins.float: ...
+        def __new__(cls, value:builtins.float) -> Schedule.Constant: ...
+    
+    class Linear(Schedule):
+        r"""
+        Moves linearly from `start` to `end` over `episodes` episodes, then stays at `end`.
+        """
+        __match_args__ = ("start", "end", "episodes",)
+        @property
+        def start(self) -> builtins.float: ...
+        @property
+        def end(self) -> builtins.float: ...
+        @property
+        def episodes(self) -> builtins.int: ...
+        def __new__(cls, start:builtins.float, end:builtins.float, episodes:builtins.int) -> Schedule.Linear: ...
+    
+    class Exponential(Schedule):
+        r"""
+        Multiplies `start` by `decay` every episode, never going below `min`.
+        """
+        __match_args__ = ("start", "decay", "min",)
+        @property
+        def start(self) -> builtins.float: ...
+        @property
+        def decay(self) -> builtins.float: ...
+        @property
+        def min(self) -> builtins.float: ...
+        def __new__(cls, start:builtins.float, decay:builtins.float, min:builtins.float) -> Schedule.Exponential: ...
+    
+    def to_json(self) -> builtins.str:
+        r"""
+        Serializes the variant and its parameters to JSON.
+        """
+    @staticmethod
+    def from_json(json:builtins.str) -> Schedule: ...
+    def __reduce__(self) -> tuple[typing.Any, typing.Any]: ...
+    ...
+
+class Scenario:
+    r"""
+    A named benchmark board, returned by `load_scenario`.
+    """
+    @property
+    def name(self) -> builtins.str: ...
+    @property
+    def version(self) -> builtins.int: ...
+    @property
+    def description(self) -> builtins.str: ...
+    @property
+    def board(self) -> Board: ...
+    @property
+    def config(self) -> GGConfig:
+        r"""
+        The config to play the scenario with. Its `scenario` field pins this version, so
+        `run`, `rollout` and `render_episode` all use exactly this board.
+        """
+    def to_json(self) -> builtins.str:
+        r"""
+        Serializes the scenario with its board and config to JSON.
+        """
+    @staticmethod
+    def from_json(json:builtins.str) -> Scenario: ...
+    def __reduce__(self) -> tuple[typing.Any, typing.Any]: ...
+    def __repr__(self) -> builtins.str: ...
+
+class Viewer:
+    r"""
+    A window that keeps running while Python does other work, returned by `open_viewer`.
+    """
+    @property
+    def is_open(self) -> builtins.bool:
+        r"""
+        Whether the window is still open.
+        """
+    def show(self, state:GameState) -> None:
+        r"""
+        Shows `state`, e.g. one returned by `GameState.step`, and pauses playback on it. Press
+        Space in the window to let the policy play on from there. Raises `InvalidConfigError`
+        when the state's board does not fit the viewer's config, and `PolicyShapeError` when
+        the current policy has no action for one of its free cells.
+        """
+    def set_policy(self, policy:typing.Any) -> None:
+        r"""
+        Replaces the policy, read like the `policy` of `run` against the board shown last, and
+        resumes playback.
+        """
+    def close(self) -> None:
+        r"""
+        Closes the window and waits for the app to stop.
+        """
 
 class WorldGenerationConfig:
     @property
@@ -166,10 +597,35 @@ class WorldGenerationConfig:
     @cell_size.setter
     def cell_size(self, value: builtins.float) -> None: ...
     @property
+    def generator_version(self) -> builtins.int:
+        r"""
+        Which board generation algorithm `generation_seed` is fed to. Every version keeps
+        producing the same board for the same seed, so boards cited by seed stay reproducible
+        as long as the version is kept; newer algorithms are only used when asked for. Version 1
+        can put several goblets on one cell; version 2 gives every goblet a cell of its own.
+        """
+    @generator_version.setter
+    def generator_version(self, value: builtins.int) -> None:
+        r"""
+        Which board generation algorithm `generation_seed` is fed to. Every version keeps
+        producing the same board for the same seed, so boards cited by seed stay reproducible
+        as long as the version is kept; newer algorithms are only used when asked for. Version 1
+        can put several goblets on one cell; version 2 gives every goblet a cell of its own.
+        """
+    @property
     def size(self) -> tuple[builtins.int, builtins.int]:
         r"""
         Returns the size of the maze as (width, height)
         """
+    def to_json(self) -> builtins.str:
+        r"""
+        Serializes the world size, obstacles and generator version to JSON.
+        """
+    @staticmethod
+    def from_json(json:builtins.str) -> WorldGenerationConfig: ...
+    def __reduce__(self) -> tuple[typing.Any, typing.Any]: ...
+    def __getstate__(self) -> builtins.str: ...
+    def __setstate__(self, state:builtins.str) -> None: ...
     def __repr__(self) -> builtins.str: ...
     def __str__(self) -> builtins.str: ...
 
@@ -183,11 +639,135 @@ class Action(Enum):
     def from_int(tag:builtins.int) -> Action: ...
     def __reduce__(self) -> tuple[typing.Any, typing.Any]: ...
 
+class CameraMode(Enum):
+    r"""
+    How the board is viewed in the windowed app.
+    """
+    TopDown = ...
+    r"""
+    Straight down with an orthographic projection, zoomed by `scale`.
+    """
+    Orbit = ...
+    r"""
+    A perspective camera circling the board, rotated by dragging with the left mouse
+    button while the board editor is closed.
+    """
+
+    def __reduce__(self) -> tuple[typing.Any, typing.Any]: ...
+
 class GhostPolicy(Enum):
     Random = ...
     Chaser = ...
 
+    def __reduce__(self) -> tuple[typing.Any, typing.Any]: ...
+
+class PolicyLayout(Enum):
+    r"""
+    How the axes of a policy array map onto the board.
+    """
+    WidthHeight = ...
+    r"""
+    `policy[x, y]`, matching the `(x, y)` tuples used by `Board`.
+    """
+    HeightWidth = ...
+    r"""
+    `policy[y, x]`, i.e. one row of the array per row of the board.
+    """
+
+    def __reduce__(self) -> tuple[typing.Any, typing.Any]: ...
+
+class Renderer(Enum):
+    Window = ...
+    Terminal = ...
+
+    def __reduce__(self) -> tuple[typing.Any, typing.Any]: ...
+
+class TdAlgorithm(Enum):
+    r"""
+    The temporal-difference update used by `train_tabular`.
+    """
+    QLearning = ...
+    r"""
+    Off-policy: bootstraps from the greedy action in the next state.
+    """
+    Sarsa = ...
+    r"""
+    On-policy: bootstraps from the action actually taken in the next state.
+    """
+    ExpectedSarsa = ...
+    r"""
+    Bootstraps from the expectation of the epsilon-greedy policy in the next state.
+    """
+
+    def __reduce__(self) -> tuple[typing.Any, typing.Any]: ...
+
+def load_scenario(name:builtins.str) -> Scenario:
+    r"""
+    Loads a scenario from the built-in registry. `name` may pin a version as `name@version`;
+    a bare name loads its latest version. Versions never change once released, so a pinned
+    scenario always has the same board.
+    """
+
+def mcts(state:GameState, iterations:builtins.int=1000, exploration:builtins.float=1.4142135623730951, max_depth:builtins.int=100, discount:builtins.float=0.99, rollout_policy:typing.Optional[typing.Any]=None, layout:PolicyLayout=PolicyLayout.WidthHeight) -> tuple[Action, builtins.list[ActionStats]]:
+    r"""
+    Plans one move from `state` with Monte Carlo tree search (UCT) and returns the most
+    visited action with the visit and value statistics of every action. Simulations leave the
+    tree with uniformly random actions unless `rollout_policy` is given. Returns are scaled to
+    [-1, 1] by `goblets.max_reward`, so `exploration` is relative to that scale.
+    """
+
+def open_viewer(config:GGConfig, policy:typing.Optional[typing.Any]=None, layout:PolicyLayout=PolicyLayout.WidthHeight, board:typing.Optional[Board]=None) -> Viewer:
+    r"""
+    Opens the game for `config` like `run`, but returns at once with a `Viewer` that controls
+    the window while Python keeps running. The app runs on a thread of its own, so the window
+    stays responsive between calls. Without a `policy` it starts paused on a uniformly random
+    one until `Viewer.set_policy` is called. Only one window, opened either here or by `run`,
+    can ever be opened per Python process: the windowing library cannot create another one
+    after the first closes. Not supported on macOS, which only opens windows on the main thread.
+    """
+
 def parse_config(config_path:builtins.str) -> GGConfig: ...
 
-def run(config:GGConfig, policy:typing.Optional[typing.Any]=None) -> typing.Optional[tuple[GameState, builtins.int, builtins.int]]: ...
+def render_episode(config:GGConfig, policy:typing.Any, path:builtins.str, layout:PolicyLayout=PolicyLayout.WidthHeight, cell_pixels:builtins.int=16, max_steps:builtins.int=200) -> builtins.int:
+    r"""
+    Plays one episode of `policy` without opening a window and writes every frame to `path`,
+    either as an animated GIF (when `path` ends in `.gif`) or as a numbered PNG sequence in
+    the directory `path`. Returns the number of frames written.
+    """
+
+def rollout(config:GGConfig, policy:typing.Any, layout:PolicyLayout=PolicyLayout.WidthHeight, max_steps:builtins.int=200) -> tuple[builtins.list[GameState], builtins.list[Action]]:
+    r"""
+    Plays one episode of `policy` without rendering it. Returns every visited state, starting
+    with the initial state, and the actions taken between them.
+    """
+
+def run(config:GGConfig, policy:typing.Optional[typing.Any]=None, layout:PolicyLayout=PolicyLayout.WidthHeight, comparison_policy:typing.Optional[typing.Any]=None, board:typing.Optional[Board]=None) -> typing.Optional[tuple[GameState, builtins.int, builtins.int]]:
+    r"""
+    Opens the game for `config`, played by `policy`. With `comparison_policy`, the window is
+    split in two and the second policy plays the same board and episode seed on the right,
+    with the cells where the policies disagree outlined on both halves. A hand-authored
+    `board` replaces the one `config` describes; `InvalidConfigError` is raised unless it has
+    the size `config` describes and a ghost exactly when `config` gives the ghost a policy.
+    """
+
+def scenario_names() -> builtins.list[builtins.str]:
+    r"""
+    The `name@version` of every scenario in the built-in registry.
+    """
+
+def train_tabular(state:GameState, algorithm:TdAlgorithm, episodes:builtins.int=1000, discount:builtins.float=0.99, epsilon:Schedule=Schedule.Constant(value=0.1), learning_rate:Schedule=Schedule.Constant(value=0.1), max_steps:builtins.int=200, seed:builtins.int=0) -> tuple[numpy.typing.NDArray[numpy.float64], numpy.typing.NDArray[numpy.float64]]:
+    r"""
+    Trains a tabular agent with `algorithm` on episodes that all start from `state`, acting
+    epsilon-greedily. `epsilon` and `learning_rate` are looked up per episode, and every random
+    draw comes from `seed`, so the same arguments always give the same result. Returns the
+    Q-table, with one row per `GameState.state_index` and columns in action tag order, and the
+    undiscounted return of every episode. A ghost capture is scored as `-goblets.max_reward`.
+    """
+
+def visitation_counts(states:typing.Sequence[GameState], layout:PolicyLayout=PolicyLayout.WidthHeight) -> numpy.typing.NDArray[numpy.uint32]:
+    r"""
+    Counts how often the agent stands on each cell over `states`, e.g. the states returned by
+    `rollout`, as an array indexed like a policy with the same `layout`. All states must be on
+    boards of the same size.
+    """
 
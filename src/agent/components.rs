@@ -2,6 +2,9 @@ use bevy::prelude::*;
 use derivative::Derivative;
 use pyo3::{prelude::*, types::PyTuple};
 use pyo3_stub_gen::derive::{gen_stub_pyclass_enum, gen_stub_pymethods};
+use serde::{Deserialize, Serialize};
+
+use crate::{error::GGError, game_state::GameState};
 
 #[derive(Debug, Clone, Copy, PartialEq, Component, Reflect, Default)]
 #[reflect(Component)]
@@ -11,7 +14,17 @@ pub struct Agent;
 #[reflect(Component)]
 pub struct GhostAgent;
 
-#[derive(Debug, Clone, Copy, PartialEq, Reflect)]
+/// Which game an agent, ghost or camera belongs to. Everything is on the `Primary` lane
+/// unless a `ComparisonPolicy` is being played next to it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Component, Reflect, Default)]
+#[reflect(Component)]
+pub enum Lane {
+    #[default]
+    Primary,
+    Comparison,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Reflect, Serialize, Deserialize)]
 #[gen_stub_pyclass_enum]
 #[pyclass(name = "Action", module = "gg_core._core")]
 pub enum Action {
@@ -21,6 +34,11 @@ pub enum Action {
     Right,
 }
 
+impl Action {
+    /// Every action, ordered by its integer tag.
+    pub const ALL: [Action; 4] = [Action::Up, Action::Down, Action::Left, Action::Right];
+}
+
 impl std::fmt::Display for Action {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         let s = match self {
@@ -33,14 +51,16 @@ impl std::fmt::Display for Action {
     }
 }
 
-impl From<u8> for Action {
-    fn from(tag: u8) -> Self {
+impl TryFrom<u8> for Action {
+    type Error = GGError;
+
+    fn try_from(tag: u8) -> Result<Self, Self::Error> {
         match tag {
-            0 => Action::Up,
-            1 => Action::Down,
-            2 => Action::Left,
-            3 => Action::Right,
-            _ => panic!("Invalid Action tag: {}", tag),
+            0 => Ok(Action::Up),
+            1 => Ok(Action::Down),
+            2 => Ok(Action::Left),
+            3 => Ok(Action::Right),
+            _ => Err(GGError::InvalidAction(tag)),
         }
     }
 }
@@ -72,7 +92,7 @@ impl From<&Action> for u8 {
 impl Action {
     #[staticmethod]
     fn from_int(tag: u8) -> PyResult<Self> {
-        Ok(Action::from(tag))
+        Ok(Action::try_from(tag)?)
     }
 
     fn __reduce__(&self, py: Python) -> PyResult<(Py<PyAny>, Py<PyAny>)> {
@@ -89,6 +109,97 @@ impl Action {
 #[derive(Debug, Clone, PartialEq, Message)]
 pub struct PlayerActionMessage {
     pub action: Action,
+    pub lane: Lane,
+}
+
+/// Sent after the agent takes a step: the action the policy chose, the direction the agent
+/// actually moved in after slipping, and whether a wall or the board edge stopped it.
+#[derive(Debug, Clone, PartialEq, Message)]
+pub struct AgentMovedMessage {
+    pub intended: Action,
+    pub executed: Action,
+    pub blocked: bool,
+    pub lane: Lane,
+}
+
+/// Moves an entity's `Transform` from `from` to `to` over `duration` seconds. A blocked
+/// move instead nudges the entity by `bump` and back.
+#[derive(Debug, Clone, Default, Component)]
+pub struct MoveAnimation {
+    pub from: Vec3,
+    pub to: Vec3,
+    pub bump: Option<Vec3>,
+    pub elapsed: f32,
+    pub duration: f32,
+}
+
+impl MoveAnimation {
+    pub fn at(position: Vec3) -> Self {
+        Self {
+            from: position,
+            to: position,
+            ..default()
+        }
+    }
+}
+
+/// The direction the agent meant to move in before it slipped, shown for `remaining`
+/// seconds.
+#[derive(Debug, Clone, Default, Component)]
+pub struct SlipIndicator {
+    pub intended: Option<Action>,
+    pub remaining: f32,
+}
+
+/// Debugger state for the windowed app: whether the policy is paused and the states visited
+/// so far in this episode, oldest first, for stepping backward.
+#[derive(Resource, Debug, Default)]
+pub struct Playback {
+    pub paused: bool,
+    pub history: Vec<GameState>,
+    /// Whether the end of the current episode has been added to the `EpisodeTally`, so that
+    /// rewinding and replaying it does not count it twice.
+    pub counted: bool,
+}
+
+/// The countdown to restarting a finished episode, started by `auto_reset` once the episode is
+/// over and cleared when a new one starts.
+#[derive(Resource, Debug, Default)]
+pub struct AutoResetCountdown(pub Option<Timer>);
+
+/// Episodes finished in the windowed app and the sum of their returns, kept across resets.
+#[derive(Resource, Debug, Default)]
+pub struct EpisodeTally {
+    pub episodes: usize,
+    pub total_return: f64,
+}
+
+impl EpisodeTally {
+    pub fn average_return(&self) -> Option<f64> {
+        (self.episodes > 0).then(|| self.total_return / self.episodes as f64)
+    }
+}
+
+/// The game played by the `ComparisonPolicy`, stepped in lockstep with `GameState` and reset
+/// with it, so both policies always face the same board and episode seed. Keeps its own
+/// history and tally, mirroring `Playback` and `EpisodeTally`.
+#[derive(Resource, Debug)]
+pub struct ComparisonLane {
+    pub state: GameState,
+    pub history: Vec<GameState>,
+    pub counted: bool,
+    pub tally: EpisodeTally,
+}
+
+impl ComparisonLane {
+    pub fn new(state: GameState) -> Self {
+        Self {
+            state,
+            history: Vec::new(),
+            counted: false,
+            tally: EpisodeTally::default(),
+        }
+    }
 }
 
 #[derive(Debug, Clone, Bundle, Derivative)]
@@ -97,15 +208,21 @@ pub struct AgentBundle {
     #[derivative(Default(value = "Name::new(\"Agent\")"))]
     pub name: Name,
     pub agent: Agent,
+    pub lane: Lane,
     pub position: Transform,
+    pub animation: MoveAnimation,
+    pub slip: SlipIndicator,
 }
 
 impl AgentBundle {
-    pub fn new(name: &str, position: Vec3) -> Self {
+    pub fn new(name: &str, position: Vec3, lane: Lane) -> Self {
         Self {
             name: Name::new(name.to_string()),
             agent: Agent,
+            lane,
             position: Transform::from_translation(position),
+            animation: MoveAnimation::at(position),
+            slip: SlipIndicator::default(),
         }
     }
 }
@@ -116,15 +233,19 @@ pub struct GhostAgentBundle {
     #[derivative(Default(value = "Name::new(\"GhostAgent\")"))]
     pub name: Name,
     pub agent: GhostAgent,
+    pub lane: Lane,
     pub position: Transform,
+    pub animation: MoveAnimation,
 }
 
 impl GhostAgentBundle {
-    pub fn new(name: &str, position: Vec3) -> Self {
+    pub fn new(name: &str, position: Vec3, lane: Lane) -> Self {
         Self {
             name: Name::new(name.to_string()),
             agent: GhostAgent,
+            lane,
             position: Transform::from_translation(position),
+            animation: MoveAnimation::at(position),
         }
     }
 }
@@ -2,15 +2,19 @@ mod components;
 mod systems;
 mod visual;
 
+use bevy::input::common_conditions::input_just_pressed;
 use bevy::prelude::*;
 use derivative::Derivative;
 use pyo3::prelude::*;
-use pyo3_stub_gen::derive::{gen_stub_pyclass, gen_stub_pyclass_enum};
+use pyo3_stub_gen::derive::{gen_stub_pyclass, gen_stub_pyclass_enum, gen_stub_pymethods};
 use serde::{Deserialize, Serialize};
 
 pub use components::*;
 
-use crate::core::{GGConfig, StartupSets};
+use crate::core::{GGConfig, StartupSets, UpdateSets};
+use crate::editor;
+use crate::error::GGResult;
+use crate::pickle;
 
 #[gen_stub_pyclass_enum]
 #[pyclass]
@@ -20,6 +24,14 @@ pub enum GhostPolicy {
     Chaser,
 }
 
+#[gen_stub_pymethods]
+#[pymethods]
+impl GhostPolicy {
+    fn __reduce__(&self, py: Python) -> PyResult<(Py<PyAny>, Py<PyAny>)> {
+        pickle::reduce_variant(py, "GhostPolicy", &format!("{:?}", self))
+    }
+}
+
 #[gen_stub_pyclass]
 #[pyclass(name = "AgentConfig")]
 #[derive(Debug, Clone, Resource, Reflect, Derivative, Serialize, Deserialize)]
@@ -35,11 +47,36 @@ pub struct AgentConfig {
     pub ghost_policy: Option<GhostPolicy>,
 
     #[pyo3(get, set)]
+    #[derivative(Default(value = "[1.0, 0.0, 0.0, 0.0]"))]
     pub transition: [f32; 4],
 }
 
+#[gen_stub_pymethods]
 #[pymethods]
 impl AgentConfig {
+    /// Serializes the agent's name, ghost policy and transition model to JSON.
+    fn to_json(&self) -> GGResult<String> {
+        pickle::to_json(self)
+    }
+
+    #[staticmethod]
+    fn from_json(json: &str) -> GGResult<Self> {
+        pickle::from_json(json)
+    }
+
+    fn __reduce__(&self, py: Python) -> PyResult<(Py<PyAny>, Py<PyAny>)> {
+        pickle::reduce(py, "AgentConfig", self)
+    }
+
+    fn __getstate__(&self) -> GGResult<String> {
+        pickle::to_json(self)
+    }
+
+    fn __setstate__(&mut self, state: &str) -> GGResult<()> {
+        *self = pickle::from_json(state)?;
+        Ok(())
+    }
+
     fn __repr__(&self) -> PyResult<String> {
         Ok(format!("AgentConfig({})", self.__str__()?))
     }
@@ -58,9 +95,36 @@ pub struct AgentPlugin;
 impl Plugin for AgentPlugin {
     fn build(&self, app: &mut App) {
         app.add_message::<components::PlayerActionMessage>();
+        app.add_message::<components::AgentMovedMessage>();
+        app.init_resource::<Playback>();
+        app.init_resource::<EpisodeTally>();
+        app.init_resource::<AutoResetCountdown>();
         app.add_systems(PreStartup, spawn_agent_assets);
         app.add_systems(Startup, systems::spawn_agents.in_set(StartupSets::Agents));
-        app.add_systems(Update, (systems::evaluate_policy, systems::step));
+        app.add_systems(Update, systems::respawn_agents.after(UpdateSets::EditBoard));
+        app.add_systems(
+            Update,
+            (
+                (
+                    systems::toggle_pause
+                        .run_if(input_just_pressed(KeyCode::Space).and(editor::prompt_closed)),
+                    systems::step_forward.run_if(input_just_pressed(KeyCode::Period)),
+                    systems::step_backward.run_if(input_just_pressed(KeyCode::Comma)),
+                    systems::speed_up.run_if(input_just_pressed(KeyCode::BracketRight)),
+                    systems::slow_down.run_if(input_just_pressed(KeyCode::BracketLeft)),
+                    systems::restart_episode
+                        .run_if(input_just_pressed(KeyCode::KeyR).and(editor::prompt_closed)),
+                ),
+                systems::evaluate_policy,
+                systems::step,
+                systems::count_finished_episode,
+                systems::auto_reset,
+                systems::start_move_animations,
+                systems::animate_movement,
+                systems::draw_slip_indicators,
+            )
+                .chain(),
+        );
     }
 }
 
@@ -1,8 +1,14 @@
 use bevy::prelude::*;
 
-use crate::agent::{Agent, GhostAgent, PlayerActionMessage};
-use crate::core::{GGConfig, Policy, PolicyTimer};
-use crate::game_state::GameState;
+use std::time::Duration;
+
+use crate::agent::{
+    Action, Agent, AgentMovedMessage, AutoResetCountdown, ComparisonLane, EpisodeTally, GhostAgent,
+    Lane, MoveAnimation, Playback, PlayerActionMessage, SlipIndicator,
+};
+use crate::camera::lane_layer;
+use crate::core::{ComparisonPolicy, GGConfig, Policy, PolicyTimer};
+use crate::game_state::{BoardEditedMessage, GameState, Visitation};
 use crate::scene::WALL_HEIGHT;
 
 use super::components::{AgentBundle, GhostAgentBundle};
@@ -28,7 +34,64 @@ pub fn spawn_agents(
     graphics: Option<Res<AgentGraphicsAssets>>,
     state: Res<GameState>,
     config: Res<GGConfig>,
+    comparison: Option<Res<ComparisonPolicy>>,
+) {
+    let visuals = match (graphics, meshes) {
+        (Some(graphics), Some(mut meshes)) => {
+            let mesh = meshes.add(Cuboid::new(
+                config.world_generation.cell_size,
+                config.world_generation.cell_size,
+                config.world_generation.cell_size,
+            ));
+            Some((mesh, graphics))
+        }
+        _ => None,
+    };
+    let visuals = visuals
+        .as_ref()
+        .map(|(mesh, graphics)| (mesh, graphics.as_ref()));
+
+    if comparison.is_none() {
+        spawn_lane(&mut commands, &state, &config, visuals, Lane::Primary);
+        return;
+    }
+
+    // Each lane's agent and ghost are only seen by the camera of its half of the window.
+    for lane in [Lane::Primary, Lane::Comparison] {
+        let entities = spawn_lane(&mut commands, &state, &config, visuals, lane);
+        for entity in entities.into_iter().flatten() {
+            commands.entity(entity).insert(lane_layer(lane));
+        }
+    }
+    commands.insert_resource(ComparisonLane::new(state.clone()));
+}
+
+/// Replaces the agents and ghosts of the old board with those of an edited one.
+#[allow(clippy::type_complexity)]
+pub fn respawn_agents(
+    mut messages: MessageReader<BoardEditedMessage>,
+    mut commands: Commands,
+    agents: Query<Entity, Or<(With<Agent>, With<GhostAgent>)>>,
 ) {
+    if messages.read().count() == 0 {
+        return;
+    }
+
+    for entity in agents.iter() {
+        commands.entity(entity).despawn();
+    }
+    commands.run_system_cached(spawn_agents);
+}
+
+/// Spawns the agent and, if the board has one, the ghost of `lane` at their cells in
+/// `state`. Returns the agent and ghost entities.
+fn spawn_lane(
+    commands: &mut Commands,
+    state: &GameState,
+    config: &GGConfig,
+    visuals: Option<(&Handle<Mesh>, &AgentGraphicsAssets)>,
+    lane: Lane,
+) -> [Option<Entity>; 2] {
     let agent_world_position = cell_to_world(
         state.board.agent_position,
         config.world_generation.cell_size,
@@ -40,7 +103,11 @@ pub fn spawn_agents(
     info!("Spawning agent at position: {:?}", agent_world_position);
 
     let entity = commands
-        .spawn(AgentBundle::new(&config.agent.name, agent_world_position))
+        .spawn(AgentBundle::new(
+            &config.agent.name,
+            agent_world_position,
+            lane,
+        ))
         .id();
 
     let ghost_entity = if let Some(ghost_position) = state.board.ghost_position {
@@ -57,22 +124,14 @@ pub fn spawn_agents(
         );
         Some(
             commands
-                .spawn(GhostAgentBundle::new("Ghost", ghost_world_position))
+                .spawn(GhostAgentBundle::new("Ghost", ghost_world_position, lane))
                 .id(),
         )
     } else {
         None
     };
 
-    if let Some(graphics) = graphics
-        && let Some(mut meshes) = meshes
-    {
-        let mesh = meshes.add(Cuboid::new(
-            config.world_generation.cell_size,
-            config.world_generation.cell_size,
-            config.world_generation.cell_size,
-        ));
-
+    if let Some((mesh, graphics)) = visuals {
         commands.entity(entity).insert((
             Mesh3d(mesh.clone()),
             MeshMaterial3d(graphics.material.clone()),
@@ -85,59 +144,439 @@ pub fn spawn_agents(
             ));
         }
     }
+
+    [Some(entity), ghost_entity]
 }
 
+/// Longest history kept for stepping backward; older states are dropped first.
+const MAX_HISTORY: usize = 1000;
+
+/// Share of the policy interval a move takes to animate.
+const MOVE_FRACTION: f32 = 0.8;
+/// How far into a wall a blocked move bumps, in cells.
+const BUMP_DISTANCE: f32 = 0.25;
+/// Shortest time the intended direction of a slip stays on screen.
+const SLIP_INDICATOR_SECS: f32 = 0.75;
+
+/// Bounds for changing `render_delay_secs` from the keyboard.
+const MIN_RENDER_DELAY_SECS: f32 = 1.0 / 64.0;
+const MAX_RENDER_DELAY_SECS: f32 = 8.0;
+
+fn policy_action(game_state: &mut GameState, policy: &Policy) -> Option<Action> {
+    let agent_position = game_state.board.agent_position;
+    let action_index = policy.index(&game_state.board, agent_position);
+    let action = policy.sample(action_index, &mut game_state.rng);
+    if action.is_none() {
+        error!("Policy has no action for cell {:?}", agent_position);
+    }
+    action
+}
+
+/// Chooses the next action of every lane whose episode is still running.
+fn choose_actions(
+    message_writer: &mut MessageWriter<PlayerActionMessage>,
+    game_state: &mut GameState,
+    policy: &Policy,
+    comparison: Option<(&Policy, &mut ComparisonLane)>,
+) {
+    if !game_state.done
+        && let Some(action) = policy_action(game_state, policy)
+    {
+        message_writer.write(PlayerActionMessage {
+            action,
+            lane: Lane::Primary,
+        });
+    }
+
+    if let Some((policy, comparison)) = comparison
+        && !comparison.state.done
+        && let Some(action) = policy_action(&mut comparison.state, policy)
+    {
+        message_writer.write(PlayerActionMessage {
+            action,
+            lane: Lane::Comparison,
+        });
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
 pub fn evaluate_policy(
     mut message_writer: MessageWriter<PlayerActionMessage>,
     mut timer: ResMut<PolicyTimer>,
     time: Res<Time>,
-    game_state: Res<GameState>,
+    mut game_state: ResMut<GameState>,
     policy: Res<Policy>,
+    playback: Res<Playback>,
+    comparison_policy: Option<Res<ComparisonPolicy>>,
+    mut comparison: Option<ResMut<ComparisonLane>>,
 ) {
     timer.0.tick(time.delta());
-    if !timer.0.is_finished() {
+    if !timer.0.is_finished() || playback.paused {
         return;
     }
 
-    let agent_position = game_state.board.agent_position;
-    let action_index = agent_position.0 + agent_position.1 * game_state.board.width;
-    let action = policy
-        .0
-        .get(action_index)
-        .cloned()
-        .expect("Policy action out of bounds");
+    // Sampling a stochastic policy draws from the episode's random stream, so the state is
+    // only borrowed mutably when some lane is still running and has an action to draw.
+    let comparison_done = comparison.as_ref().is_none_or(|lane| lane.state.done);
+    if game_state.done && comparison_done {
+        return;
+    }
 
-    message_writer.write(PlayerActionMessage { action });
+    choose_actions(
+        &mut message_writer,
+        &mut game_state,
+        &policy,
+        comparison_policy
+            .as_deref()
+            .map(|policy| &policy.0)
+            .zip(comparison.as_deref_mut()),
+    );
 }
 
-#[allow(clippy::type_complexity)]
+/// Steps each lane by the action chosen for it. Only steps of the primary lane count towards
+/// the visitation heatmap, so restarts, rewinds and edits never add visits.
 pub fn step(
     mut message_reader: MessageReader<PlayerActionMessage>,
-    mut query: Query<(&mut Transform, Option<&Agent>, Option<&GhostAgent>)>,
+    mut moved_writer: MessageWriter<AgentMovedMessage>,
     mut game_state: ResMut<GameState>,
+    mut playback: ResMut<Playback>,
+    mut comparison: Option<ResMut<ComparisonLane>>,
+    mut visitation: ResMut<Visitation>,
 ) {
-    for &PlayerActionMessage { action } in message_reader.read() {
+    for &PlayerActionMessage { action, lane } in message_reader.read() {
+        let (game_state, history) = match lane {
+            Lane::Primary => (game_state.as_mut(), &mut playback.history),
+            Lane::Comparison => {
+                let Some(comparison) = comparison.as_deref_mut() else {
+                    continue;
+                };
+                (&mut comparison.state, &mut comparison.history)
+            }
+        };
+
+        if game_state.done {
+            continue;
+        }
+
         let state = game_state.step(action);
+        if let Some(executed) = state.executed_action {
+            moved_writer.write(AgentMovedMessage {
+                intended: action,
+                executed,
+                blocked: state.board.agent_position == game_state.board.agent_position,
+                lane,
+            });
+        }
 
-        for (mut transform, is_agent, is_ghost) in query.iter_mut() {
-            transform.translation = cell_to_world(
-                if is_agent.is_some() {
-                    state.board.agent_position
-                } else if is_ghost.is_some() {
-                    state
-                        .board
-                        .ghost_position
-                        .expect("Ghost position should exist")
-                } else {
-                    continue;
-                },
-                game_state.config.world_generation.cell_size,
-                game_state.config.world_generation.world_width,
-                game_state.config.world_generation.world_height,
-                is_ghost.is_some(),
-            );
+        if lane == Lane::Primary {
+            visitation.record(&state);
+        }
+
+        if history.len() == MAX_HISTORY {
+            history.remove(0);
+        }
+        history.push(std::mem::replace(game_state, state));
+    }
+}
+
+/// Starts moving the agent and ghost entities towards their cells in the new state of their
+/// lane. Moves take most of the policy interval, so consecutive steps run into each other
+/// without overlapping.
+#[allow(clippy::type_complexity)]
+pub fn start_move_animations(
+    mut message_reader: MessageReader<AgentMovedMessage>,
+    mut query: Query<(
+        &Transform,
+        &mut MoveAnimation,
+        Option<&mut SlipIndicator>,
+        &Lane,
+        Option<&Agent>,
+        Option<&GhostAgent>,
+    )>,
+    game_state: Res<GameState>,
+    comparison: Option<Res<ComparisonLane>>,
+    config: Res<GGConfig>,
+) {
+    let moves = message_reader.read().cloned().collect::<Vec<_>>();
+    let cell_size = game_state.config.world_generation.cell_size;
+    let duration = config.render_delay_secs * MOVE_FRACTION;
+
+    for (transform, mut animation, slip, &lane, is_agent, is_ghost) in query.iter_mut() {
+        let game_state = match (lane, &comparison) {
+            (Lane::Primary, _) if game_state.is_changed() => &*game_state,
+            (Lane::Comparison, Some(comparison)) if comparison.is_changed() => &comparison.state,
+            _ => continue,
+        };
+
+        let target = cell_to_world(
+            if is_agent.is_some() {
+                game_state.board.agent_position
+            } else if is_ghost.is_some() {
+                game_state
+                    .board
+                    .ghost_position
+                    .expect("Ghost position should exist")
+            } else {
+                continue;
+            },
+            cell_size,
+            game_state.config.world_generation.world_width,
+            game_state.config.world_generation.world_height,
+            is_ghost.is_some(),
+        );
+
+        *animation = MoveAnimation {
+            from: transform.translation,
+            to: target,
+            bump: None,
+            elapsed: 0.0,
+            duration,
+        };
+
+        if is_agent.is_none() {
+            continue;
+        }
+        let Some(&AgentMovedMessage {
+            intended,
+            executed,
+            blocked,
+            ..
+        }) = moves.iter().rev().find(|moved| moved.lane == lane)
+        else {
+            continue;
+        };
+
+        if blocked {
+            animation.bump = Some(world_direction(executed) * cell_size * BUMP_DISTANCE);
+        }
+        // Gizmos are drawn in both halves of a comparison, where an arrow would float over the
+        // other lane's board, so slips are only shown for a single policy.
+        if let Some(mut slip) = slip
+            && comparison.is_none()
+            && intended != executed
+        {
+            slip.intended = Some(intended);
+            slip.remaining = SLIP_INDICATOR_SECS.max(duration);
+        }
+    }
+}
+
+pub fn animate_movement(time: Res<Time>, mut query: Query<(&mut Transform, &mut MoveAnimation)>) {
+    for (mut transform, mut animation) in query.iter_mut() {
+        animation.elapsed += time.delta_secs();
+        let progress = if animation.duration > 0.0 {
+            (animation.elapsed / animation.duration).min(1.0)
+        } else {
+            1.0
+        };
+
+        // Smoothstep, so moves ease in and out of each cell.
+        let eased = progress * progress * (3.0 - 2.0 * progress);
+        let bump = animation.bump.map_or(Vec3::ZERO, |bump| {
+            bump * (std::f32::consts::PI * progress).sin()
+        });
+
+        transform.translation = animation.from.lerp(animation.to, eased) + bump;
+    }
+}
+
+/// Draws a fading arrow in the direction the agent meant to move in after it slipped.
+pub fn draw_slip_indicators(
+    mut gizmos: Gizmos,
+    time: Res<Time>,
+    config: Res<GGConfig>,
+    mut query: Query<(&Transform, &mut SlipIndicator)>,
+) {
+    let length = config.world_generation.cell_size;
+
+    for (transform, mut slip) in query.iter_mut() {
+        let Some(intended) = slip.intended else {
+            continue;
+        };
+
+        slip.remaining -= time.delta_secs();
+        if slip.remaining <= 0.0 {
+            slip.intended = None;
+            continue;
         }
 
-        *game_state = state;
+        let start = transform.translation + Vec3::Y * length;
+        let alpha = (slip.remaining / SLIP_INDICATOR_SECS).min(1.0);
+        gizmos.arrow(
+            start,
+            start + world_direction(intended) * length,
+            Color::srgb(1.0, 0.85, 0.0).with_alpha(alpha),
+        );
+    }
+}
+
+/// The world-space direction of a move; board rows run along +Z.
+fn world_direction(action: Action) -> Vec3 {
+    match action {
+        Action::Up => Vec3::NEG_Z,
+        Action::Down => Vec3::Z,
+        Action::Left => Vec3::NEG_X,
+        Action::Right => Vec3::X,
+    }
+}
+
+pub fn toggle_pause(mut playback: ResMut<Playback>) {
+    playback.paused = !playback.paused;
+}
+
+/// Pauses the policy and takes exactly one step with it.
+pub fn step_forward(
+    mut message_writer: MessageWriter<PlayerActionMessage>,
+    mut game_state: ResMut<GameState>,
+    policy: Res<Policy>,
+    mut playback: ResMut<Playback>,
+    comparison_policy: Option<Res<ComparisonPolicy>>,
+    mut comparison: Option<ResMut<ComparisonLane>>,
+) {
+    playback.paused = true;
+
+    choose_actions(
+        &mut message_writer,
+        &mut game_state,
+        &policy,
+        comparison_policy
+            .as_deref()
+            .map(|policy| &policy.0)
+            .zip(comparison.as_deref_mut()),
+    );
+}
+
+/// Pauses the policy and returns to the state before the last step. When comparing, both
+/// lanes step back together; a lane whose episode ended earlier has a shorter history and
+/// stays on its final state until the other lane is rewound past that step.
+pub fn step_backward(
+    mut game_state: ResMut<GameState>,
+    mut playback: ResMut<Playback>,
+    mut comparison: Option<ResMut<ComparisonLane>>,
+) {
+    playback.paused = true;
+
+    let primary_steps = playback.history.len();
+    let comparison_steps = comparison.as_ref().map(|c| c.history.len());
+    let steps = primary_steps.max(comparison_steps.unwrap_or(0));
+
+    if primary_steps == steps
+        && let Some(previous) = playback.history.pop()
+    {
+        *game_state = previous;
+    }
+    if let Some(comparison) = comparison.as_deref_mut()
+        && comparison_steps == Some(steps)
+        && let Some(previous) = comparison.history.pop()
+    {
+        comparison.state = previous;
+    }
+}
+
+pub fn speed_up(mut timer: ResMut<PolicyTimer>, mut config: ResMut<GGConfig>) {
+    scale_render_delay(&mut timer, &mut config, 0.5);
+}
+
+pub fn slow_down(mut timer: ResMut<PolicyTimer>, mut config: ResMut<GGConfig>) {
+    scale_render_delay(&mut timer, &mut config, 2.0);
+}
+
+fn scale_render_delay(timer: &mut PolicyTimer, config: &mut GGConfig, factor: f32) {
+    let delay_secs =
+        (config.render_delay_secs * factor).clamp(MIN_RENDER_DELAY_SECS, MAX_RENDER_DELAY_SECS);
+    config.render_delay_secs = delay_secs;
+    timer.0.set_duration(Duration::from_secs_f32(delay_secs));
+    info!("Render delay set to {:.3}s", delay_secs);
+}
+
+pub fn restart_episode(
+    mut game_state: ResMut<GameState>,
+    mut playback: ResMut<Playback>,
+    mut comparison: Option<ResMut<ComparisonLane>>,
+) {
+    restart(&mut game_state, &mut playback, comparison.as_deref_mut());
+}
+
+/// Starts a new episode on the same board and forgets the history of the old one. The
+/// comparison lane, if any, restarts from the same state so both policies see the same
+/// episode seed. The agent and ghost entities are moved to their new cells by
+/// `start_move_animations`.
+fn restart(
+    game_state: &mut GameState,
+    playback: &mut Playback,
+    comparison: Option<&mut ComparisonLane>,
+) {
+    let (state, seed) = game_state.reset();
+    info!("Restarting episode with seed {}", seed);
+
+    if let Some(comparison) = comparison {
+        comparison.state = state.clone();
+        comparison.history.clear();
+        comparison.counted = false;
+    }
+
+    *game_state = state;
+    playback.history.clear();
+    playback.counted = false;
+}
+
+/// Adds a finished episode to the tally of its lane the first time its final state is
+/// reached.
+pub fn count_finished_episode(
+    game_state: Res<GameState>,
+    mut playback: ResMut<Playback>,
+    mut tally: ResMut<EpisodeTally>,
+    comparison: Option<ResMut<ComparisonLane>>,
+) {
+    if game_state.done && !playback.counted {
+        playback.counted = true;
+        tally.episodes += 1;
+        tally.total_return += game_state.scored_reward();
+    }
+
+    if let Some(mut comparison) = comparison
+        && comparison.state.done
+        && !comparison.counted
+    {
+        let reward = comparison.state.scored_reward();
+        comparison.counted = true;
+        comparison.tally.episodes += 1;
+        comparison.tally.total_return += reward;
+    }
+}
+
+/// Restarts a finished episode once it has been shown for `auto_reset_secs`. When comparing,
+/// the countdown starts once both lanes have finished. It is held while playback is paused.
+pub fn auto_reset(
+    mut countdown: ResMut<AutoResetCountdown>,
+    time: Res<Time>,
+    config: Res<GGConfig>,
+    mut game_state: ResMut<GameState>,
+    mut playback: ResMut<Playback>,
+    mut comparison: Option<ResMut<ComparisonLane>>,
+) {
+    let Some(delay_secs) = config.auto_reset_secs else {
+        return;
+    };
+    if !game_state.done
+        || comparison
+            .as_ref()
+            .is_some_and(|comparison| !comparison.state.done)
+    {
+        countdown.0 = None;
+        return;
+    }
+    if playback.paused {
+        return;
+    }
+
+    let timer = countdown
+        .0
+        .get_or_insert_with(|| Timer::from_seconds(delay_secs, TimerMode::Once));
+    timer.tick(time.delta());
+
+    if timer.is_finished() {
+        countdown.0 = None;
+        restart(&mut game_state, &mut playback, comparison.as_deref_mut());
     }
 }
@@ -0,0 +1,42 @@
+use bevy::prelude::*;
+
+use crate::camera::CameraMode;
+
+/// Height of a top-down camera above the point it looks at.
+const TOP_DOWN_HEIGHT: f32 = 10.0;
+
+/// Where a camera looks and, for `CameraMode::Orbit`, the direction and distance it looks
+/// from. The camera's `Transform` is derived from it by `apply_camera_rigs`.
+#[derive(Debug, Clone, Component)]
+pub struct CameraRig {
+    pub focus: Vec3,
+    /// Angle around the vertical axis; 0 looks along -Z, like the top-down view.
+    pub yaw: f32,
+    /// Angle above the board.
+    pub pitch: f32,
+    pub distance: f32,
+}
+
+impl CameraRig {
+    pub fn transform(&self, mode: CameraMode) -> Transform {
+        match mode {
+            CameraMode::TopDown => {
+                Transform::from_translation(self.focus + Vec3::Y * TOP_DOWN_HEIGHT)
+                    .looking_at(self.focus, Vec3::NEG_Z)
+            }
+            CameraMode::Orbit => {
+                let offset = Vec3::new(
+                    self.pitch.cos() * self.yaw.sin(),
+                    self.pitch.sin(),
+                    self.pitch.cos() * self.yaw.cos(),
+                );
+                Transform::from_translation(self.focus + offset * self.distance)
+                    .looking_at(self.focus, Vec3::Y)
+            }
+        }
+    }
+}
+
+/// Whether the cameras keep the agent of their lane in the middle of the view.
+#[derive(Debug, Clone, Copy, Resource)]
+pub struct FollowAgent(pub bool);
@@ -1,13 +1,56 @@
+mod components;
 mod systems;
 
-use bevy::input::common_conditions::input_pressed;
+use bevy::camera::visibility::RenderLayers;
+use bevy::input::common_conditions::input_just_pressed;
 use bevy::prelude::*;
 
 use derivative::Derivative;
 use pyo3::prelude::*;
-use pyo3_stub_gen::derive::gen_stub_pyclass;
+use pyo3_stub_gen::derive::{gen_stub_pyclass, gen_stub_pyclass_enum, gen_stub_pymethods};
 use serde::{Deserialize, Serialize};
 
+pub use components::*;
+
+use crate::agent::Lane;
+use crate::core::ComparisonPolicy;
+use crate::editor;
+use crate::error::GGResult;
+use crate::pickle;
+
+/// Render layers of the agents and ghosts of each lane in a side-by-side comparison. The
+/// board stays on the default layer, which both cameras see.
+const PRIMARY_LAYER: usize = 1;
+const COMPARISON_LAYER: usize = 2;
+
+pub fn lane_layer(lane: Lane) -> RenderLayers {
+    RenderLayers::layer(match lane {
+        Lane::Primary => PRIMARY_LAYER,
+        Lane::Comparison => COMPARISON_LAYER,
+    })
+}
+
+/// How the board is viewed in the windowed app.
+#[gen_stub_pyclass_enum]
+#[pyclass]
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq, Reflect)]
+pub enum CameraMode {
+    /// Straight down with an orthographic projection, zoomed by `scale`.
+    #[default]
+    TopDown,
+    /// A perspective camera circling the board, rotated by dragging with the left mouse
+    /// button while the board editor is closed.
+    Orbit,
+}
+
+#[gen_stub_pymethods]
+#[pymethods]
+impl CameraMode {
+    fn __reduce__(&self, py: Python) -> PyResult<(Py<PyAny>, Py<PyAny>)> {
+        pickle::reduce_variant(py, "CameraMode", &format!("{:?}", self))
+    }
+}
+
 #[gen_stub_pyclass]
 #[pyclass(name = "CameraConfig")]
 #[derive(Debug, Clone, Resource, Reflect, Derivative, Serialize, Deserialize)]
@@ -18,10 +61,61 @@ pub struct CameraConfig {
     #[pyo3(get, set)]
     #[derivative(Default(value = "-0.15"))]
     pub scale: f32,
+    #[pyo3(get, set)]
+    pub mode: CameraMode,
+    /// World units panned per second while an arrow key is held.
+    #[pyo3(get, set)]
+    #[derivative(Default(value = "50.0"))]
+    pub pan_speed: f32,
+    /// Zoom rate while `+` or `-` is held: the view shrinks by a factor of `e` every
+    /// `1 / zoom_speed` seconds. One line of the scroll wheel zooms as much as a tenth of a
+    /// second of holding a key.
+    #[pyo3(get, set)]
+    #[derivative(Default(value = "1.0"))]
+    pub zoom_speed: f32,
+    /// Radians an orbit camera turns per pixel of mouse drag.
+    #[pyo3(get, set)]
+    #[derivative(Default(value = "0.005"))]
+    pub orbit_sensitivity: f32,
+    /// Vertical field of view of an orbit camera.
+    #[pyo3(get, set)]
+    #[derivative(Default(value = "45.0"))]
+    pub fov_degrees: f32,
+    /// Starting angle of an orbit camera above the board; 90 looks straight down.
+    #[pyo3(get, set)]
+    #[derivative(Default(value = "60.0"))]
+    pub pitch_degrees: f32,
+    /// Whether the camera starts out keeping the agent in the middle of the view.
+    #[pyo3(get, set)]
+    pub follow_agent: bool,
 }
 
+#[gen_stub_pymethods]
 #[pymethods]
 impl CameraConfig {
+    /// Serializes the camera settings to JSON.
+    fn to_json(&self) -> GGResult<String> {
+        pickle::to_json(self)
+    }
+
+    #[staticmethod]
+    fn from_json(json: &str) -> GGResult<Self> {
+        pickle::from_json(json)
+    }
+
+    fn __reduce__(&self, py: Python) -> PyResult<(Py<PyAny>, Py<PyAny>)> {
+        pickle::reduce(py, "CameraConfig", self)
+    }
+
+    fn __getstate__(&self) -> GGResult<String> {
+        pickle::to_json(self)
+    }
+
+    fn __setstate__(&mut self, state: &str) -> GGResult<()> {
+        *self = pickle::from_json(state)?;
+        Ok(())
+    }
+
     fn __repr__(&self) -> PyResult<String> {
         Ok(format!("CameraConfig({})", self.__str__()?))
     }
@@ -43,10 +137,17 @@ impl Plugin for CameraPlugin {
         app.add_systems(
             Update,
             (
-                systems::zoom_in.run_if(input_pressed(KeyCode::Equal)),
-                systems::zoom_out.run_if(input_pressed(KeyCode::Minus)),
+                systems::split_viewports.run_if(resource_exists::<ComparisonPolicy>),
+                systems::fit_to_window.run_if(input_just_pressed(KeyCode::KeyF)),
+                systems::toggle_follow.run_if(input_just_pressed(KeyCode::KeyG)),
+                systems::zoom_camera.run_if(editor::prompt_closed),
                 systems::pan_camera,
-            ),
+                systems::orbit_camera.run_if(editor::editor_closed),
+                systems::follow_agent,
+                systems::apply_camera_rigs,
+            )
+                .chain()
+                .run_if(resource_exists::<FollowAgent>),
         );
     }
 }
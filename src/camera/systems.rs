@@ -1,41 +1,167 @@
+use bevy::camera::{Viewport, visibility::RenderLayers};
+use bevy::input::mouse::{AccumulatedMouseMotion, AccumulatedMouseScroll, MouseScrollUnit};
 use bevy::prelude::*;
+use bevy::window::PrimaryWindow;
 
-use crate::core::GGConfig;
+use crate::agent::{Agent, Lane};
+use crate::camera::{CameraMode, CameraRig, FollowAgent, lane_layer};
+use crate::core::{ComparisonPolicy, GGConfig};
 
-pub fn setup_camera(mut commands: Commands, config: Res<GGConfig>) {
+/// Share of the window left around the board by `fit_to_window`.
+const FIT_MARGIN: f32 = 1.1;
+/// Seconds of holding a zoom key that one line of the scroll wheel is worth.
+const SCROLL_LINE_SECS: f32 = 0.1;
+/// Pixels of a touchpad scroll that count as one line.
+const PIXELS_PER_LINE: f32 = 16.0;
+/// Orbit cameras stay between just above the board and straight down.
+const MIN_PITCH: f32 = 0.05;
+const MAX_PITCH: f32 = std::f32::consts::FRAC_PI_2;
+
+pub fn setup_camera(
+    mut commands: Commands,
+    config: Res<GGConfig>,
+    comparison: Option<Res<ComparisonPolicy>>,
+) {
     if config.headless {
         return;
     }
 
-    commands.spawn((
-        Camera3d::default(),
-        Transform::from_translation(Vec3::new(0.0, 10.0, 0.0)).looking_at(Vec3::ZERO, Vec3::NEG_Z),
-        Projection::from(OrthographicProjection {
+    commands.insert_resource(FollowAgent(config.camera.follow_agent));
+
+    let rig = CameraRig {
+        focus: Vec3::ZERO,
+        yaw: 0.0,
+        pitch: config.camera.pitch_degrees.to_radians(),
+        distance: fit_distance(&config, 1.0),
+    };
+    let projection = match config.camera.mode {
+        CameraMode::TopDown => Projection::from(OrthographicProjection {
             scale: config.camera.scale,
             ..OrthographicProjection::default_3d()
         }),
+        CameraMode::Orbit => Projection::from(PerspectiveProjection {
+            fov: config.camera.fov_degrees.to_radians(),
+            ..default()
+        }),
+    };
+    let camera = (
+        Camera3d::default(),
+        rig.transform(config.camera.mode),
+        projection,
+        rig,
+    );
+
+    if comparison.is_none() {
+        commands.spawn(camera);
+        return;
+    }
+
+    // One camera per half of the window, each seeing the board and its own lane's agents.
+    // The UI gets a camera of its own so that it spans the whole window instead of a half.
+    for (order, lane) in [Lane::Primary, Lane::Comparison].into_iter().enumerate() {
+        commands.spawn((
+            camera.clone(),
+            Camera {
+                order: order as isize,
+                ..default()
+            },
+            lane,
+            RenderLayers::layer(0).union(&lane_layer(lane)),
+        ));
+    }
+    commands.spawn((
+        Camera2d,
+        Camera {
+            order: 2,
+            clear_color: ClearColorConfig::None,
+            ..default()
+        },
+        IsDefaultUiCamera,
     ));
 }
 
-pub fn zoom_in(mut query: Query<&mut Projection, With<Camera3d>>) {
-    for mut proj in query.iter_mut() {
-        if let Projection::Orthographic(ortho) = &mut *proj {
-            ortho.scale += 0.001;
+/// How far an orbit camera has to be from the middle of the board to see all of it in a
+/// viewport `aspect` times as wide as it is high.
+fn fit_distance(config: &GGConfig, aspect: f32) -> f32 {
+    let half_fov = (config.camera.fov_degrees.to_radians() / 2.0).tan();
+    let world = &config.world_generation;
+    let half_extent =
+        (world.world_height / 2.0).max(world.world_width / 2.0 / aspect.max(f32::EPSILON));
+    half_extent / half_fov * FIT_MARGIN
+}
+
+/// Centers the board and zooms so that all of it fits in the view. Orbit cameras also go
+/// back to their starting angle.
+pub fn fit_to_window(
+    config: Res<GGConfig>,
+    mut cameras: Query<(&Camera, &mut Projection, &mut CameraRig)>,
+) {
+    let world = &config.world_generation;
+
+    for (camera, mut projection, mut rig) in cameras.iter_mut() {
+        let size = camera.logical_viewport_size().unwrap_or(Vec2::ONE);
+
+        rig.focus = Vec3::ZERO;
+        rig.yaw = 0.0;
+        rig.pitch = config.camera.pitch_degrees.to_radians();
+        rig.distance = fit_distance(&config, size.x / size.y.max(1.0));
+
+        if let Projection::Orthographic(ortho) = &mut *projection {
+            // Keeps the sign of the configured scale, which decides which way up the board is.
+            let scale = (world.world_width / size.x.max(1.0))
+                .max(world.world_height / size.y.max(1.0))
+                * FIT_MARGIN;
+            ortho.scale = scale.copysign(config.camera.scale);
         }
     }
 }
 
-pub fn zoom_out(mut query: Query<&mut Projection, With<Camera3d>>) {
-    for mut proj in query.iter_mut() {
-        if let Projection::Orthographic(ortho) = &mut *proj {
-            ortho.scale -= 0.001;
+pub fn toggle_follow(mut follow: ResMut<FollowAgent>) {
+    follow.0 = !follow.0;
+}
+
+/// Zooms with `+`/`-` and the scroll wheel, at a rate independent of the frame rate.
+pub fn zoom_camera(
+    time: Res<Time>,
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    scroll: Res<AccumulatedMouseScroll>,
+    config: Res<GGConfig>,
+    mut cameras: Query<(&mut Projection, &mut CameraRig)>,
+) {
+    let mut held = 0.0;
+    if keyboard_input.pressed(KeyCode::Equal) {
+        held += 1.0;
+    }
+    if keyboard_input.pressed(KeyCode::Minus) {
+        held -= 1.0;
+    }
+    let lines = match scroll.unit {
+        MouseScrollUnit::Line => scroll.delta.y,
+        MouseScrollUnit::Pixel => scroll.delta.y / PIXELS_PER_LINE,
+    };
+
+    let amount = config.camera.zoom_speed * (held * time.delta_secs() + lines * SCROLL_LINE_SECS);
+    if amount == 0.0 {
+        return;
+    }
+
+    let factor = (-amount).exp();
+    for (mut projection, mut rig) in cameras.iter_mut() {
+        match &mut *projection {
+            Projection::Orthographic(ortho) => ortho.scale *= factor,
+            _ => rig.distance *= factor,
         }
     }
 }
 
+/// Pans with the arrow keys, relative to the direction the camera faces. Panning stops
+/// following the agent.
 pub fn pan_camera(
-    mut query: Query<&mut Transform, With<Camera3d>>,
+    time: Res<Time>,
     keyboard_input: Res<ButtonInput<KeyCode>>,
+    config: Res<GGConfig>,
+    mut follow: ResMut<FollowAgent>,
+    mut cameras: Query<&mut CameraRig>,
 ) {
     let mut direction = Vec3::ZERO;
     if keyboard_input.pressed(KeyCode::ArrowUp) {
@@ -50,7 +176,102 @@ pub fn pan_camera(
     if keyboard_input.pressed(KeyCode::ArrowRight) {
         direction.x += 1.0;
     }
-    for mut transform in query.iter_mut() {
-        transform.translation += direction;
+    if direction == Vec3::ZERO {
+        return;
+    }
+
+    if follow.0 {
+        follow.0 = false;
+    }
+    let distance = config.camera.pan_speed * time.delta_secs();
+    for mut rig in cameras.iter_mut() {
+        let offset = Quat::from_rotation_y(rig.yaw) * direction.normalize() * distance;
+        rig.focus += offset;
+    }
+}
+
+/// Turns orbit cameras around the point they look at while the left mouse button is held.
+/// Only runs while the board editor is closed, since the editor paints with the same button.
+pub fn orbit_camera(
+    buttons: Res<ButtonInput<MouseButton>>,
+    motion: Res<AccumulatedMouseMotion>,
+    config: Res<GGConfig>,
+    mut cameras: Query<&mut CameraRig>,
+) {
+    if config.camera.mode != CameraMode::Orbit
+        || !buttons.pressed(MouseButton::Left)
+        || motion.delta == Vec2::ZERO
+    {
+        return;
+    }
+
+    let turn = motion.delta * config.camera.orbit_sensitivity;
+    for mut rig in cameras.iter_mut() {
+        rig.yaw -= turn.x;
+        rig.pitch = (rig.pitch + turn.y).clamp(MIN_PITCH, MAX_PITCH);
+    }
+}
+
+/// Keeps each camera looking at the agent of its lane while following is on.
+pub fn follow_agent(
+    follow: Res<FollowAgent>,
+    agents: Query<(&Transform, &Lane), With<Agent>>,
+    mut cameras: Query<(&mut CameraRig, Option<&Lane>)>,
+) {
+    if !follow.0 {
+        return;
+    }
+
+    for (mut rig, lane) in cameras.iter_mut() {
+        let lane = lane.copied().unwrap_or_default();
+        let Some((transform, _)) = agents.iter().find(|(_, agent_lane)| **agent_lane == lane)
+        else {
+            continue;
+        };
+
+        let focus = transform.translation.with_y(0.0);
+        if rig.focus != focus {
+            rig.focus = focus;
+        }
+    }
+}
+
+pub fn apply_camera_rigs(
+    config: Res<GGConfig>,
+    mut cameras: Query<(&CameraRig, &mut Transform), Changed<CameraRig>>,
+) {
+    for (rig, mut transform) in cameras.iter_mut() {
+        *transform = rig.transform(config.camera.mode);
+    }
+}
+
+/// Keeps the two cameras of a side-by-side comparison on the left and right halves of the
+/// window as it is resized.
+pub fn split_viewports(
+    windows: Query<&Window, With<PrimaryWindow>>,
+    mut cameras: Query<(&mut Camera, &Lane)>,
+) {
+    let Ok(window) = windows.single() else {
+        return;
+    };
+
+    let size = window.physical_size();
+    let half = UVec2::new((size.x / 2).max(1), size.y.max(1));
+    for (mut camera, lane) in cameras.iter_mut() {
+        let x = match lane {
+            Lane::Primary => 0,
+            Lane::Comparison => size.x - half.x,
+        };
+        let position = UVec2::new(x, 0);
+        let unchanged = camera.viewport.as_ref().is_some_and(|viewport| {
+            viewport.physical_position == position && viewport.physical_size == half
+        });
+        if !unchanged {
+            camera.viewport = Some(Viewport {
+                physical_position: position,
+                physical_size: half,
+                ..default()
+            });
+        }
     }
 }
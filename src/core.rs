@@ -1,14 +1,19 @@
 use bevy::prelude::*;
 use derivative::Derivative;
 use pyo3::prelude::*;
-use pyo3_stub_gen::derive::gen_stub_pyclass;
+use pyo3_stub_gen::derive::{gen_stub_pyclass, gen_stub_pyclass_enum, gen_stub_pymethods};
+use rand::{Rng, seq::IndexedRandom};
 use serde::{Deserialize, Serialize};
 
 use crate::agent;
 use crate::agent::Action;
 use crate::camera;
+use crate::editor;
+use crate::error::{GGError, GGResult};
 use crate::game_state;
 use crate::goblet;
+use crate::pickle;
+use crate::scenario;
 use crate::scene;
 
 #[derive(SystemSet, Debug, Clone, Hash, PartialEq, Eq)]
@@ -18,6 +23,30 @@ pub enum StartupSets {
     Walls,
 }
 
+#[derive(SystemSet, Debug, Clone, Hash, PartialEq, Eq)]
+pub enum UpdateSets {
+    /// Replaces `GameState` with an edited board. The entities on the board are respawned
+    /// after it.
+    EditBoard,
+}
+
+#[gen_stub_pyclass_enum]
+#[pyclass]
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq, Reflect)]
+pub enum Renderer {
+    #[default]
+    Window,
+    Terminal,
+}
+
+#[gen_stub_pymethods]
+#[pymethods]
+impl Renderer {
+    fn __reduce__(&self, py: Python) -> PyResult<(Py<PyAny>, Py<PyAny>)> {
+        pickle::reduce_variant(py, "Renderer", &format!("{:?}", self))
+    }
+}
+
 #[gen_stub_pyclass]
 #[pyclass(name = "GGConfig")]
 #[derive(Debug, Clone, Resource, Reflect, Serialize, Deserialize, Derivative)]
@@ -36,6 +65,19 @@ pub struct GGConfig {
     #[pyo3(get, set)]
     #[derivative(Default(value = "1.0"))]
     pub render_delay_secs: f32,
+    /// Seconds to show the end of an episode before the windowed app starts a new one, or
+    /// `None` to wait for a manual restart.
+    #[pyo3(get, set)]
+    pub auto_reset_secs: Option<f32>,
+    /// Path of a level file, as saved by the board editor or `Board.to_json`. When set, the
+    /// board is read from it instead of being generated from `generation_seed`.
+    #[pyo3(get, set)]
+    pub level: Option<String>,
+    /// A scenario from the built-in registry, as `name@version` or a bare name for its latest
+    /// version. When set, the board is the scenario's fixed layout; `load_scenario` returns a
+    /// config with this and the rest of the scenario's settings filled in.
+    #[pyo3(get, set)]
+    pub scenario: Option<String>,
     #[pyo3(get, set)]
     pub generation_seed: Option<u32>,
     #[pyo3(get, set)]
@@ -44,18 +86,394 @@ pub struct GGConfig {
     pub debug: bool,
     #[pyo3(get, set)]
     pub headless: bool,
+    #[pyo3(get, set)]
+    pub renderer: Renderer,
 }
 
+/// Action probabilities ordered like `Action::ALL`, one entry per agent cell or, for
+/// ghost-aware policies, per pair of agent and ghost cells. A deterministic policy puts all
+/// of an entry's mass on one action. Entries the policy leaves empty, such as walls and
+/// goblets, are `None`; `policy::check_coverage` tells whether a board needs any of them.
 #[derive(Debug, Clone, Resource, Reflect)]
 #[reflect(Resource)]
-pub struct Policy(pub Vec<Action>);
+pub struct Policy {
+    pub probabilities: Vec<Option<[f32; 4]>>,
+    pub ghost_aware: bool,
+}
+
+impl Policy {
+    /// Index of the entry used when the agent is at `agent` and the ghost is wherever `board`
+    /// places it: `a` for plain policies and `a * width * height + g` for ghost-aware ones,
+    /// where `a` and `g` are the `x + y * width` indices of the agent and ghost cells.
+    pub fn index(&self, board: &game_state::Board, agent: (usize, usize)) -> usize {
+        let cell = |(x, y): (usize, usize)| x + y * board.width;
+        match board.ghost_position {
+            Some(ghost) if self.ghost_aware => {
+                cell(agent) * board.width * board.height + cell(ghost)
+            }
+            _ => cell(agent),
+        }
+    }
+
+    pub fn one_hot(action: Action) -> [f32; 4] {
+        Action::ALL.map(|a| if a == action { 1.0 } else { 0.0 })
+    }
+
+    /// Returns the single action of a cell that puts all of its mass on one action.
+    pub fn deterministic_action(&self, index: usize) -> Option<Action> {
+        let probabilities = self.probabilities.get(index)?.as_ref()?;
+        Action::ALL
+            .iter()
+            .zip(probabilities)
+            .find(|(_, p)| **p >= 1.0)
+            .map(|(action, _)| *action)
+    }
+
+    /// Returns the most likely action of a cell.
+    pub fn greedy(&self, index: usize) -> Option<Action> {
+        let probabilities = self.probabilities.get(index)?.as_ref()?;
+        Action::ALL
+            .iter()
+            .zip(probabilities)
+            .max_by(|(_, a), (_, b)| a.total_cmp(b))
+            .map(|(action, _)| *action)
+    }
+
+    /// Draws the action of a cell from `rng`. Deterministic cells return their action without
+    /// drawing, so a one-hot policy leaves `rng` exactly as stepping its actions by hand does.
+    pub fn sample(&self, index: usize, rng: &mut impl Rng) -> Option<Action> {
+        if let Some(action) = self.deterministic_action(index) {
+            return Some(action);
+        }
+
+        let probabilities = self.probabilities.get(index)?.as_ref()?;
+        Action::ALL
+            .iter()
+            .zip(probabilities)
+            .collect::<Vec<_>>()
+            .choose_weighted(rng, |(_, p)| **p)
+            .ok()
+            .map(|(action, _)| **action)
+    }
+
+    /// Formats the action of a cell, listing every action with a non-zero probability
+    /// when the policy is stochastic there.
+    pub fn describe(&self, index: usize) -> String {
+        if let Some(action) = self.deterministic_action(index) {
+            return action.to_string();
+        }
+
+        let Some(Some(probabilities)) = self.probabilities.get(index) else {
+            return "None".to_string();
+        };
+
+        Action::ALL
+            .iter()
+            .zip(probabilities)
+            .filter(|(_, p)| **p > 0.0)
+            .map(|(action, p)| format!("{} {:.2}", action, p))
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+}
 
 #[derive(Debug, Clone, Resource, Reflect)]
 #[reflect(Resource)]
 pub struct PolicyTimer(pub Timer);
 
+/// The second policy of a side-by-side comparison, played on the right half of the window
+/// against the same board and episode seed as `Policy`.
+#[derive(Debug, Clone, Resource, Reflect)]
+#[reflect(Resource)]
+pub struct ComparisonPolicy(pub Policy);
+
+impl GGConfig {
+    /// Runs `validate` and turns any violations into a single `GGError::InvalidConfig`.
+    pub fn check(&self) -> GGResult<()> {
+        let errors = self.validate();
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(GGError::InvalidConfig(errors))
+        }
+    }
+}
+
+/// A single violated invariant in a `GGConfig`, naming the offending field by its dotted path.
+#[gen_stub_pyclass]
+#[pyclass(name = "ConfigError")]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ConfigError {
+    #[pyo3(get)]
+    pub field: String,
+    #[pyo3(get)]
+    pub message: String,
+}
+
+impl ConfigError {
+    fn new(field: &str, message: impl Into<String>) -> Self {
+        Self {
+            field: field.to_string(),
+            message: message.into(),
+        }
+    }
+}
+
+impl std::fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: {}", self.field, self.message)
+    }
+}
+
+#[gen_stub_pymethods]
+#[pymethods]
+impl ConfigError {
+    /// Serializes the field name and message to JSON.
+    fn to_json(&self) -> GGResult<String> {
+        pickle::to_json(self)
+    }
+
+    #[staticmethod]
+    fn from_json(json: &str) -> GGResult<Self> {
+        pickle::from_json(json)
+    }
+
+    fn __reduce__(&self, py: Python) -> PyResult<(Py<PyAny>, Py<PyAny>)> {
+        pickle::reduce(py, "ConfigError", self)
+    }
+
+    fn __getstate__(&self) -> GGResult<String> {
+        pickle::to_json(self)
+    }
+
+    fn __setstate__(&mut self, state: &str) -> GGResult<()> {
+        *self = pickle::from_json(state)?;
+        Ok(())
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "ConfigError(field={:?}, message={:?})",
+            self.field, self.message
+        )
+    }
+
+    fn __str__(&self) -> String {
+        self.to_string()
+    }
+}
+
+#[gen_stub_pymethods]
 #[pymethods]
 impl GGConfig {
+    /// Checks the invariants board generation and simulation rely on, returning one error per
+    /// violation. An empty list means the config is valid.
+    pub fn validate(&self) -> Vec<ConfigError> {
+        let mut errors = Vec::new();
+        let world = &self.world_generation;
+
+        if !(world.cell_size.is_finite() && world.cell_size > 0.0) {
+            errors.push(ConfigError::new(
+                "world_generation.cell_size",
+                format!("must be a positive number, got {}", world.cell_size),
+            ));
+        }
+
+        for (field, extent) in [
+            ("world_generation.world_width", world.world_width),
+            ("world_generation.world_height", world.world_height),
+        ] {
+            if !(extent.is_finite() && extent > 0.0) {
+                errors.push(ConfigError::new(
+                    field,
+                    format!("must be a positive number, got {}", extent),
+                ));
+            } else if world.cell_size.is_finite() && world.cell_size > 0.0 {
+                let cells = extent / world.cell_size;
+                if (cells - cells.round()).abs() > 1e-4 {
+                    errors.push(ConfigError::new(
+                        field,
+                        format!(
+                            "{} is not a multiple of cell_size {}; the nearest valid value is {}",
+                            extent,
+                            world.cell_size,
+                            cells.round().max(1.0) * world.cell_size
+                        ),
+                    ));
+                }
+            }
+        }
+
+        let (width, height) = world.size();
+        if errors.is_empty() && (width < 3 || height < 3) {
+            errors.push(ConfigError::new(
+                "world_generation",
+                format!(
+                    "the board must be at least 3x3 cells to leave room inside the border walls, got {}x{}",
+                    width, height
+                ),
+            ));
+        }
+
+        if world.num_obstacles > 0 && world.obstacle_radius_cells == 0 {
+            errors.push(ConfigError::new(
+                "world_generation.obstacle_radius_cells",
+                "must be at least 1 when num_obstacles is greater than 0",
+            ));
+        }
+
+        if !(1..=scene::LATEST_GENERATOR_VERSION).contains(&world.generator_version) {
+            errors.push(ConfigError::new(
+                "world_generation.generator_version",
+                format!(
+                    "must be between 1 and {}, got {}",
+                    scene::LATEST_GENERATOR_VERSION,
+                    world.generator_version
+                ),
+            ));
+        }
+
+        let transition = &self.agent.transition;
+        if let Some(weight) = transition.iter().find(|w| !(w.is_finite() && **w >= 0.0)) {
+            errors.push(ConfigError::new(
+                "agent.transition",
+                format!("weights must be finite and non-negative, got {}", weight),
+            ));
+        } else if transition.iter().sum::<f32>() <= 0.0 {
+            errors.push(ConfigError::new(
+                "agent.transition",
+                "at least one weight must be positive, e.g. [1.0, 0.0, 0.0, 0.0] for deterministic moves",
+            ));
+        }
+
+        if self.goblets.max_reward > i32::MAX as u32 {
+            errors.push(ConfigError::new(
+                "goblets.max_reward",
+                format!(
+                    "must be at most {}, got {}",
+                    i32::MAX,
+                    self.goblets.max_reward
+                ),
+            ));
+        }
+
+        if width >= 3 && height >= 3 {
+            // Saturates for the unbounded sizes an invalid cell_size gives, reported above.
+            let interior_cells = (width - 2).saturating_mul(height - 2);
+            let occupants =
+                self.goblets.number + 1 + usize::from(self.agent.ghost_policy.is_some());
+            if occupants > interior_cells {
+                errors.push(ConfigError::new(
+                    "goblets.number",
+                    format!(
+                        "{} goblets plus the agent{} need {} cells but at most {} cells are free inside the border walls, fewer once obstacles are placed",
+                        self.goblets.number,
+                        if self.agent.ghost_policy.is_some() { " and ghost" } else { "" },
+                        occupants,
+                        interior_cells
+                    ),
+                ));
+            }
+        }
+
+        let camera = &self.camera;
+        if !(camera.scale.is_finite() && camera.scale != 0.0) {
+            errors.push(ConfigError::new(
+                "camera.scale",
+                format!("must be a non-zero number, got {}", camera.scale),
+            ));
+        }
+
+        for (field, value) in [
+            ("camera.pan_speed", camera.pan_speed),
+            ("camera.zoom_speed", camera.zoom_speed),
+            ("camera.orbit_sensitivity", camera.orbit_sensitivity),
+        ] {
+            if !(value.is_finite() && value > 0.0) {
+                errors.push(ConfigError::new(
+                    field,
+                    format!("must be a positive number, got {}", value),
+                ));
+            }
+        }
+
+        if !(camera.fov_degrees > 0.0 && camera.fov_degrees < 180.0) {
+            errors.push(ConfigError::new(
+                "camera.fov_degrees",
+                format!(
+                    "must be between 0 and 180 degrees, exclusive, got {}",
+                    camera.fov_degrees
+                ),
+            ));
+        }
+
+        if !(camera.pitch_degrees > 0.0 && camera.pitch_degrees <= 90.0) {
+            errors.push(ConfigError::new(
+                "camera.pitch_degrees",
+                format!(
+                    "must be above 0 and at most 90 degrees, got {}",
+                    camera.pitch_degrees
+                ),
+            ));
+        }
+
+        if let Some(id) = &self.scenario {
+            if let Err(e) = scenario::check(id) {
+                errors.push(ConfigError::new("scenario", e.to_string()));
+            }
+            if self.level.is_some() {
+                errors.push(ConfigError::new(
+                    "scenario",
+                    "cannot be combined with level; set only one of them",
+                ));
+            }
+        }
+
+        if !(self.render_delay_secs.is_finite() && self.render_delay_secs >= 0.0) {
+            errors.push(ConfigError::new(
+                "render_delay_secs",
+                format!(
+                    "must be a non-negative number, got {}",
+                    self.render_delay_secs
+                ),
+            ));
+        }
+
+        if let Some(secs) = self.auto_reset_secs
+            && !(secs.is_finite() && secs >= 0.0)
+        {
+            errors.push(ConfigError::new(
+                "auto_reset_secs",
+                format!("must be a non-negative number, got {}", secs),
+            ));
+        }
+
+        errors
+    }
+
+    /// Serializes the whole config, sub-configs and seeds included, to JSON.
+    fn to_json(&self) -> GGResult<String> {
+        pickle::to_json(self)
+    }
+
+    #[staticmethod]
+    fn from_json(json: &str) -> GGResult<Self> {
+        pickle::from_json(json)
+    }
+
+    fn __reduce__(&self, py: Python) -> PyResult<(Py<PyAny>, Py<PyAny>)> {
+        pickle::reduce(py, "GGConfig", self)
+    }
+
+    fn __getstate__(&self) -> GGResult<String> {
+        pickle::to_json(self)
+    }
+
+    fn __setstate__(&mut self, state: &str) -> GGResult<()> {
+        *self = pickle::from_json(state)?;
+        Ok(())
+    }
+
     fn __repr__(&self) -> PyResult<String> {
         Ok(format!("GGConfig({})", self.__str__()?))
     }
@@ -72,13 +490,21 @@ impl GGConfig {
 
 pub struct GGPlugin {
     pub config: GGConfig,
-    pub policy: Vec<Action>,
+    /// The state the first episode starts from, built by the same code that hands boards to
+    /// Python, so the window shows exactly the board a policy was computed on.
+    pub game_state: game_state::GameState,
+    pub policy: Policy,
+    pub comparison_policy: Option<Policy>,
 }
 
 impl Plugin for GGPlugin {
     fn build(&self, app: &mut App) {
         app.insert_resource(self.config.clone());
-        app.insert_resource(Policy(self.policy.clone()));
+        app.insert_resource(self.game_state.clone());
+        app.insert_resource(self.policy.clone());
+        if let Some(policy) = &self.comparison_policy {
+            app.insert_resource(ComparisonPolicy(policy.clone()));
+        }
         app.insert_resource(PolicyTimer(Timer::from_seconds(
             self.config.render_delay_secs,
             TimerMode::Repeating,
@@ -87,6 +513,7 @@ impl Plugin for GGPlugin {
         app.add_plugins((
             agent::AgentPlugin,
             camera::CameraPlugin,
+            editor::EditorPlugin,
             goblet::GobletPlugin,
             scene::ScenePlugin,
             game_state::GameStatePlugin,
@@ -105,3 +532,175 @@ impl Plugin for GGPlugin {
         app.insert_resource(ClearColor(Color::srgb_u8(0, 136, 255)));
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use bevy_prng::WyRand;
+    use rand::{RngCore, SeedableRng};
+
+    use super::*;
+
+    #[test]
+    fn sampling_a_deterministic_cell_leaves_the_rng_untouched() {
+        let policy = Policy {
+            probabilities: vec![
+                Some(Policy::one_hot(Action::Left)),
+                Some([0.5, 0.5, 0.0, 0.0]),
+            ],
+            ghost_aware: false,
+        };
+        let mut rng = WyRand::from_seed(7u64.to_ne_bytes());
+        let mut untouched = rng.clone();
+
+        assert_eq!(policy.sample(0, &mut rng), Some(Action::Left));
+        assert_eq!(rng.next_u64(), untouched.next_u64());
+
+        assert!(matches!(
+            policy.sample(1, &mut rng),
+            Some(Action::Up | Action::Down)
+        ));
+        assert_ne!(rng.next_u64(), untouched.next_u64());
+    }
+
+    type Change = Box<dyn FnOnce(&mut GGConfig)>;
+
+    /// The fields `validate` reports for the default config changed by `change`.
+    fn invalid_fields(change: impl FnOnce(&mut GGConfig)) -> Vec<String> {
+        let mut config = GGConfig::default();
+        change(&mut config);
+        config
+            .validate()
+            .into_iter()
+            .map(|error| error.field)
+            .collect()
+    }
+
+    #[test]
+    fn default_config_is_valid() {
+        assert_eq!(GGConfig::default().validate(), Vec::new());
+    }
+
+    #[test]
+    fn validate_reports_each_invalid_field() {
+        let cases: Vec<(&str, Change)> = vec![
+            (
+                "world_generation.cell_size",
+                Box::new(|config| config.world_generation.cell_size = 0.0),
+            ),
+            (
+                "world_generation.world_width",
+                Box::new(|config| config.world_generation.world_width = -1.0),
+            ),
+            (
+                "world_generation.world_height",
+                Box::new(|config| {
+                    config.world_generation.world_height += config.world_generation.cell_size / 2.0
+                }),
+            ),
+            (
+                "world_generation",
+                Box::new(|config| {
+                    config.world_generation.world_width = 2.0 * config.world_generation.cell_size
+                }),
+            ),
+            (
+                "world_generation.obstacle_radius_cells",
+                Box::new(|config| {
+                    config.world_generation.num_obstacles = 1;
+                    config.world_generation.obstacle_radius_cells = 0;
+                }),
+            ),
+            (
+                "world_generation.generator_version",
+                Box::new(|config| config.world_generation.generator_version = 0),
+            ),
+            (
+                "agent.transition",
+                Box::new(|config| config.agent.transition = [1.0, -0.5, 0.0, 0.0]),
+            ),
+            (
+                "agent.transition",
+                Box::new(|config| config.agent.transition = [0.0; 4]),
+            ),
+            (
+                "goblets.max_reward",
+                Box::new(|config| config.goblets.max_reward = u32::MAX),
+            ),
+            (
+                "goblets.number",
+                Box::new(|config| config.goblets.number = 10_000),
+            ),
+            ("camera.scale", Box::new(|config| config.camera.scale = 0.0)),
+            (
+                "camera.pan_speed",
+                Box::new(|config| config.camera.pan_speed = f32::NAN),
+            ),
+            (
+                "camera.zoom_speed",
+                Box::new(|config| config.camera.zoom_speed = 0.0),
+            ),
+            (
+                "camera.orbit_sensitivity",
+                Box::new(|config| config.camera.orbit_sensitivity = -1.0),
+            ),
+            (
+                "camera.fov_degrees",
+                Box::new(|config| config.camera.fov_degrees = 180.0),
+            ),
+            (
+                "camera.pitch_degrees",
+                Box::new(|config| config.camera.pitch_degrees = 0.0),
+            ),
+            (
+                "scenario",
+                Box::new(|config| config.scenario = Some("no_such_scenario".to_string())),
+            ),
+            (
+                "scenario",
+                Box::new(|config| {
+                    config.scenario = Some("cliff_walk@1".to_string());
+                    config.level = Some("level.json".to_string());
+                }),
+            ),
+            (
+                "render_delay_secs",
+                Box::new(|config| config.render_delay_secs = -1.0),
+            ),
+            (
+                "auto_reset_secs",
+                Box::new(|config| config.auto_reset_secs = Some(f32::INFINITY)),
+            ),
+        ];
+
+        for (field, change) in cases {
+            assert_eq!(invalid_fields(change), vec![field.to_string()]);
+        }
+    }
+
+    #[test]
+    fn goblet_count_check_leaves_room_for_the_agent_and_ghost() {
+        // A 5x5 board has 9 cells inside its border walls.
+        let small = |config: &mut GGConfig| {
+            let cell_size = config.world_generation.cell_size;
+            config.world_generation.world_width = 5.0 * cell_size;
+            config.world_generation.world_height = 5.0 * cell_size;
+            config.world_generation.num_obstacles = 0;
+        };
+
+        assert!(
+            invalid_fields(|config| {
+                small(config);
+                config.goblets.number = 8;
+            })
+            .is_empty()
+        );
+        assert_eq!(
+            invalid_fields(|config| {
+                small(config);
+                config.goblets.number = 8;
+                config.agent.ghost_policy = Some(agent::GhostPolicy::Chaser);
+            }),
+            vec!["goblets.number".to_string()]
+        );
+    }
+}
@@ -0,0 +1,69 @@
+use bevy::prelude::*;
+
+/// What a click on a cell does in the board editor.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum EditorTool {
+    /// Adds or removes a wall.
+    #[default]
+    Wall,
+    /// Moves the agent.
+    Agent,
+    /// Places or moves the ghost, or removes it when it is clicked.
+    Ghost,
+    /// Places a goblet after asking for its reward, or removes the goblet that is clicked.
+    Goblet,
+}
+
+impl EditorTool {
+    /// Every tool, in the order of the number keys that select them.
+    pub const ALL: [EditorTool; 4] = [
+        EditorTool::Wall,
+        EditorTool::Agent,
+        EditorTool::Ghost,
+        EditorTool::Goblet,
+    ];
+
+    pub fn key(self) -> KeyCode {
+        match self {
+            EditorTool::Wall => KeyCode::Digit1,
+            EditorTool::Agent => KeyCode::Digit2,
+            EditorTool::Ghost => KeyCode::Digit3,
+            EditorTool::Goblet => KeyCode::Digit4,
+        }
+    }
+}
+
+impl std::fmt::Display for EditorTool {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let s = match self {
+            EditorTool::Wall => "Wall",
+            EditorTool::Agent => "Agent",
+            EditorTool::Ghost => "Ghost",
+            EditorTool::Goblet => "Goblet",
+        };
+        write!(f, "{}", s)
+    }
+}
+
+/// A goblet waiting for its reward to be typed in before it is placed on `cell`.
+#[derive(Debug, Clone)]
+pub struct RewardPrompt {
+    pub cell: (usize, usize),
+    pub input: String,
+}
+
+/// State of the board editor, which is toggled with `E` in the windowed app.
+#[derive(Resource, Debug, Default)]
+pub struct Editor {
+    pub active: bool,
+    pub tool: EditorTool,
+    pub prompt: Option<RewardPrompt>,
+    /// The outcome of the last edit or save, shown under the tools.
+    pub status: String,
+}
+
+#[derive(Component)]
+pub struct EditorPanel;
+
+#[derive(Component)]
+pub struct EditorText;
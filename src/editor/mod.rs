@@ -0,0 +1,49 @@
+mod components;
+mod systems;
+
+use bevy::input::common_conditions::input_just_pressed;
+use bevy::prelude::*;
+
+pub use components::*;
+
+use crate::core::{GGConfig, UpdateSets};
+
+/// Where the editor saves the board when `GGConfig.level` is not set.
+pub const DEFAULT_LEVEL_PATH: &str = "level.json";
+
+/// Run condition for systems bound to keys that are typed into the goblet reward prompt,
+/// so that typing a reward does not also zoom, pause or restart.
+pub fn prompt_closed(editor: Res<Editor>) -> bool {
+    editor.prompt.is_none()
+}
+
+/// Run condition for systems driven by the left mouse button, which the open editor uses
+/// to paint cells, so that an orbit drag does not also edit the board.
+pub fn editor_closed(editor: Res<Editor>) -> bool {
+    !editor.active
+}
+
+pub struct EditorPlugin;
+impl Plugin for EditorPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Editor>();
+        app.add_systems(
+            Startup,
+            systems::setup_editor_panel.run_if(|config: Res<GGConfig>| !config.headless),
+        );
+        app.add_systems(
+            Update,
+            (
+                systems::toggle_editor.run_if(input_just_pressed(KeyCode::KeyE)),
+                systems::select_tool,
+                systems::edit_cell,
+                systems::type_reward,
+                systems::save_level.run_if(input_just_pressed(KeyCode::KeyS)),
+                systems::apply_board_edits.in_set(UpdateSets::EditBoard),
+                systems::update_editor_panel,
+            )
+                .chain()
+                .run_if(|config: Res<GGConfig>| !config.headless),
+        );
+    }
+}
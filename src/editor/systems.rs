@@ -0,0 +1,394 @@
+use bevy::input::ButtonState;
+use bevy::input::keyboard::{Key, KeyboardInput};
+use bevy::prelude::*;
+
+use crate::agent::{EpisodeTally, Playback};
+use crate::core::{ComparisonPolicy, GGConfig, Policy};
+use crate::editor::{
+    DEFAULT_LEVEL_PATH, Editor, EditorPanel, EditorText, EditorTool, RewardPrompt,
+};
+use crate::game_state::{Board, BoardEditedMessage, GameState, Goblet, HoverCell, Visitation};
+use crate::policy;
+
+/// What already stands on a cell of the board.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Occupant {
+    Wall,
+    Agent,
+    Ghost,
+    Goblet,
+}
+
+impl std::fmt::Display for Occupant {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let s = match self {
+            Occupant::Wall => "a wall",
+            Occupant::Agent => "the agent",
+            Occupant::Ghost => "the ghost",
+            Occupant::Goblet => "a goblet",
+        };
+        write!(f, "{}", s)
+    }
+}
+
+fn occupant(board: &Board, cell: (usize, usize)) -> Option<Occupant> {
+    if board.wall_positions.contains(&cell) {
+        Some(Occupant::Wall)
+    } else if board.agent_position == cell {
+        Some(Occupant::Agent)
+    } else if board.ghost_position == Some(cell) {
+        Some(Occupant::Ghost)
+    } else if board.goblets.iter().any(|goblet| goblet.position == cell) {
+        Some(Occupant::Goblet)
+    } else {
+        None
+    }
+}
+
+/// Applies `tool` to `cell`, or explains why it cannot be. Placing a goblet needs a reward
+/// and is handled by `type_reward` instead. The ghost can only be placed when `config` gives
+/// it a policy and never removed while it has one, since every step would need it. Removing
+/// a wall or goblet is refused when it opens a cell one of `policies` has no action for.
+fn edit(
+    board: &Board,
+    tool: EditorTool,
+    cell: (usize, usize),
+    config: &GGConfig,
+    policies: &[&Policy],
+) -> Result<Board, String> {
+    let mut board = board.clone();
+
+    match (tool, occupant(&board, cell)) {
+        (EditorTool::Ghost, _) if config.agent.ghost_policy.is_none() => {
+            return Err("agent.ghost_policy is None, so the board cannot have a ghost".to_string());
+        }
+        (EditorTool::Ghost, Some(Occupant::Ghost)) => {
+            return Err(format!(
+                "The ghost cannot be removed while agent.ghost_policy is {:?}",
+                config.agent.ghost_policy
+            ));
+        }
+        (EditorTool::Wall, Some(Occupant::Wall)) => {
+            board.wall_positions.remove(&cell);
+        }
+        (EditorTool::Wall, None) => {
+            board.wall_positions.insert(cell);
+        }
+        (EditorTool::Agent, None) => board.agent_position = cell,
+        (EditorTool::Ghost, None) => board.ghost_position = Some(cell),
+        (EditorTool::Goblet, Some(Occupant::Goblet)) => {
+            board.goblets.retain(|goblet| goblet.position != cell)
+        }
+        (_, Some(occupant)) => {
+            return Err(format!("{:?} already holds {}", cell, occupant));
+        }
+        (EditorTool::Goblet, None) => {
+            return Err(format!("{:?} needs a reward for its goblet", cell));
+        }
+    }
+
+    board.check_level(config).map_err(|e| e.to_string())?;
+    for policy in policies {
+        policy::check_coverage(policy, &board)
+            .map_err(|e| format!("The policy cannot play the edited board: {}", e))?;
+    }
+    Ok(board)
+}
+
+pub fn setup_editor_panel(mut commands: Commands) {
+    commands
+        .spawn((
+            Node {
+                position_type: PositionType::Absolute,
+                display: Display::None,
+                width: Val::Percent(100.0),
+                top: Val::Px(5.0),
+                justify_content: JustifyContent::Center,
+                ..default()
+            },
+            GlobalZIndex(5),
+            EditorPanel,
+            Name::new("EditorPanel"),
+        ))
+        .with_children(|p| {
+            p.spawn((
+                Node {
+                    padding: UiRect::all(Val::Px(8.0)),
+                    ..default()
+                },
+                BorderRadius::all(Val::Px(4.0)),
+                BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.75)),
+            ))
+            .with_children(|p| {
+                p.spawn((
+                    Text::new(""),
+                    TextFont {
+                        font_size: 14.0,
+                        ..default()
+                    },
+                    TextLayout::new_with_justify(Justify::Center),
+                    EditorText,
+                ));
+            });
+        });
+}
+
+/// Opens or closes the editor. Opening it pauses the policy and restarts the episode, so
+/// that edits apply to the board the episode starts from.
+pub fn toggle_editor(
+    mut editor: ResMut<Editor>,
+    mut playback: ResMut<Playback>,
+    game_state: Res<GameState>,
+    mut writer: MessageWriter<BoardEditedMessage>,
+) {
+    if editor.prompt.is_some() {
+        return;
+    }
+
+    editor.active = !editor.active;
+    editor.status.clear();
+
+    if editor.active {
+        playback.paused = true;
+        writer.write(BoardEditedMessage {
+            board: (*game_state.initial_board).clone(),
+            state: None,
+        });
+    }
+}
+
+pub fn select_tool(keyboard_input: Res<ButtonInput<KeyCode>>, mut editor: ResMut<Editor>) {
+    if !editor.active || editor.prompt.is_some() {
+        return;
+    }
+
+    if let Some(&tool) = EditorTool::ALL
+        .iter()
+        .find(|tool| keyboard_input.just_pressed(tool.key()))
+    {
+        editor.tool = tool;
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn edit_cell(
+    buttons: Res<ButtonInput<MouseButton>>,
+    hover: Res<HoverCell>,
+    game_state: Res<GameState>,
+    config: Res<GGConfig>,
+    policy: Res<Policy>,
+    comparison: Option<Res<ComparisonPolicy>>,
+    mut editor: ResMut<Editor>,
+    mut writer: MessageWriter<BoardEditedMessage>,
+) {
+    if !editor.active || editor.prompt.is_some() || !buttons.just_pressed(MouseButton::Left) {
+        return;
+    }
+
+    let Some(cell) = hover.cell else {
+        return;
+    };
+    let cell = (cell.x as usize, cell.y as usize);
+    let board = &game_state.initial_board;
+    if cell.0 >= board.width || cell.1 >= board.height {
+        return;
+    }
+
+    if editor.tool == EditorTool::Goblet && occupant(board, cell).is_none() {
+        editor.prompt = Some(RewardPrompt {
+            cell,
+            input: String::new(),
+        });
+        return;
+    }
+
+    let mut policies = vec![&*policy];
+    policies.extend(comparison.as_deref().map(|comparison| &comparison.0));
+    match edit(board, editor.tool, cell, &config, &policies) {
+        Ok(board) => {
+            editor.status = format!("Edited {:?}", cell);
+            writer.write(BoardEditedMessage { board, state: None });
+        }
+        Err(reason) => editor.status = reason,
+    }
+}
+
+/// Reads the reward of a goblet being placed: digits and `-` are typed in, Backspace
+/// deletes, Enter places the goblet and Escape cancels it.
+pub fn type_reward(
+    mut keys: MessageReader<KeyboardInput>,
+    game_state: Res<GameState>,
+    config: Res<GGConfig>,
+    mut editor: ResMut<Editor>,
+    mut writer: MessageWriter<BoardEditedMessage>,
+) {
+    let Some(mut prompt) = editor.prompt.take() else {
+        keys.clear();
+        return;
+    };
+
+    let max_reward = config.goblets.max_reward as i32;
+
+    for key in keys.read() {
+        if key.state != ButtonState::Pressed {
+            continue;
+        }
+
+        match &key.logical_key {
+            Key::Character(text) => prompt
+                .input
+                .extend(text.chars().filter(|c| c.is_ascii_digit() || *c == '-')),
+            Key::Backspace => {
+                prompt.input.pop();
+            }
+            Key::Escape => {
+                editor.status = "Cancelled the goblet".to_string();
+                return;
+            }
+            Key::Enter => match prompt.input.parse::<i32>() {
+                Ok(reward) if (-max_reward..=max_reward).contains(&reward) => {
+                    let mut board = (*game_state.initial_board).clone();
+                    if let Some(occupant) = occupant(&board, prompt.cell) {
+                        editor.status = format!("{:?} already holds {}", prompt.cell, occupant);
+                        return;
+                    }
+
+                    board.goblets.push(Goblet {
+                        position: prompt.cell,
+                        reward,
+                    });
+                    if let Err(e) = board.check_level(&config) {
+                        editor.status = e.to_string();
+                        return;
+                    }
+                    editor.status =
+                        format!("Placed a goblet worth {} at {:?}", reward, prompt.cell);
+                    writer.write(BoardEditedMessage { board, state: None });
+                    return;
+                }
+                _ => {
+                    editor.status = format!(
+                        "The reward must be a whole number between {} and {}",
+                        -max_reward, max_reward
+                    );
+                    prompt.input.clear();
+                }
+            },
+            _ => {}
+        }
+    }
+
+    editor.prompt = Some(prompt);
+}
+
+/// Writes the board to `GGConfig.level`, or `DEFAULT_LEVEL_PATH` when it is not set.
+pub fn save_level(game_state: Res<GameState>, config: Res<GGConfig>, mut editor: ResMut<Editor>) {
+    if !editor.active || editor.prompt.is_some() {
+        return;
+    }
+
+    let path = config.level.as_deref().unwrap_or(DEFAULT_LEVEL_PATH);
+    let result = serde_json::to_string_pretty(&*game_state.initial_board)
+        .map_err(|e| e.to_string())
+        .and_then(|json| std::fs::write(path, json).map_err(|e| e.to_string()));
+
+    editor.status = match result {
+        Ok(()) => {
+            info!("Saved level to {}", path);
+            format!("Saved level to {}", path)
+        }
+        Err(e) => {
+            error!("Failed to save level to {}: {}", path, e);
+            format!("Failed to save level to {}: {}", path, e)
+        }
+    };
+}
+
+/// Starts a new episode on an edited board, or shows the state sent along with it. The
+/// tallies are cleared when the board really changed, since they no longer describe it.
+pub fn apply_board_edits(
+    mut messages: MessageReader<BoardEditedMessage>,
+    mut game_state: ResMut<GameState>,
+    mut playback: ResMut<Playback>,
+    mut tally: ResMut<EpisodeTally>,
+    mut visitation: ResMut<Visitation>,
+) {
+    let Some(BoardEditedMessage { board, state }) = messages.read().last() else {
+        return;
+    };
+
+    let checked = match state {
+        Some(state) => state.board.check_shape(&game_state.config),
+        None => board.check_level(&game_state.config),
+    };
+    if let Err(e) = checked {
+        error!("Ignored an edited board: {}", e);
+        return;
+    }
+
+    let state = match state {
+        Some(state) => state.clone(),
+        None => GameState::from(board.clone())
+            .with_initial_board(board)
+            .with_config(&game_state.config)
+            .with_seed(game_state.rng_seed),
+    };
+
+    if *state.initial_board != *game_state.initial_board {
+        *tally = EpisodeTally::default();
+        *visitation = Visitation::new(board.width, board.height);
+    }
+
+    *game_state = state;
+    playback.history.clear();
+    playback.counted = false;
+}
+
+pub fn update_editor_panel(
+    editor: Res<Editor>,
+    mut q_panel: Query<&mut Node, With<EditorPanel>>,
+    mut q_text: Query<&mut Text, With<EditorText>>,
+) {
+    if !editor.is_changed() {
+        return;
+    }
+    let (Ok(mut node), Ok(mut text)) = (q_panel.single_mut(), q_text.single_mut()) else {
+        return;
+    };
+
+    if !editor.active {
+        node.display = Display::None;
+        return;
+    }
+
+    let tools = EditorTool::ALL
+        .iter()
+        .enumerate()
+        .map(|(i, &tool)| {
+            if tool == editor.tool {
+                format!("[{}: {}]", i + 1, tool)
+            } else {
+                format!("{}: {}", i + 1, tool)
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(" | ");
+
+    let mut lines = vec![
+        "Board Editor: click a cell to edit it".to_string(),
+        tools,
+        "S: Save Level | E: Close Editor".to_string(),
+    ];
+    if let Some(prompt) = &editor.prompt {
+        lines.push(format!(
+            "Goblet reward at {:?}: {}_ (Enter to place, Esc to cancel)",
+            prompt.cell, prompt.input
+        ));
+    }
+    if !editor.status.is_empty() {
+        lines.push(editor.status.clone());
+    }
+
+    text.0 = lines.join("\n");
+    node.display = Display::Flex;
+}
@@ -0,0 +1,75 @@
+use pyo3::{
+    PyErr, create_exception,
+    exceptions::{PyIndexError, PyRuntimeError, PyValueError},
+};
+use thiserror::Error;
+
+use crate::core::ConfigError;
+
+create_exception!(
+    gg_core._core,
+    InvalidConfigError,
+    PyValueError,
+    "Raised when a GGConfig violates one of the invariants checked by GGConfig.validate(), or when a level or board does not fit the config it is played with."
+);
+create_exception!(
+    gg_core._core,
+    BoardGenerationError,
+    PyRuntimeError,
+    "Raised when a board cannot be generated from an otherwise valid config."
+);
+create_exception!(
+    gg_core._core,
+    PolicyShapeError,
+    PyValueError,
+    "Raised when a policy does not match the shape of the board it is run on."
+);
+
+#[derive(Debug, Error)]
+pub enum GGError {
+    #[error("Invalid config:\n{}", format_config_errors(.0))]
+    InvalidConfig(Vec<ConfigError>),
+    #[error("Invalid Action tag: {0}")]
+    InvalidAction(u8),
+    #[error("Failed to generate board: {0}")]
+    BoardGeneration(String),
+    #[error("Invalid policy: {0}")]
+    PolicyShape(String),
+    #[error("State index {index} is out of range for a board with {num_states} states")]
+    StateIndex { index: usize, num_states: usize },
+    #[error("Invalid state: {0}")]
+    InvalidState(String),
+    #[error("Invalid argument: {0}")]
+    InvalidArgument(String),
+    #[error("Invalid level: {0}")]
+    Level(String),
+    #[error("Invalid JSON: {0}")]
+    Json(#[from] serde_json::Error),
+}
+
+pub type GGResult<T> = Result<T, GGError>;
+
+fn format_config_errors(errors: &[ConfigError]) -> String {
+    errors
+        .iter()
+        .map(|e| format!("  {}", e))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+impl From<GGError> for PyErr {
+    fn from(error: GGError) -> Self {
+        let message = error.to_string();
+        match error {
+            GGError::InvalidConfig(_) => InvalidConfigError::new_err(message),
+            GGError::InvalidAction(_) => PyValueError::new_err(message),
+            GGError::BoardGeneration(_) => BoardGenerationError::new_err(message),
+            GGError::PolicyShape(_) => PolicyShapeError::new_err(message),
+            GGError::StateIndex { .. } => PyIndexError::new_err(message),
+            GGError::InvalidState(_) => PyValueError::new_err(message),
+            GGError::InvalidArgument(_) => PyValueError::new_err(message),
+            GGError::Level(_) => InvalidConfigError::new_err(message),
+            GGError::Json(_) => PyValueError::new_err(message),
+        }
+    }
+}
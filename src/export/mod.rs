@@ -0,0 +1,186 @@
+mod font;
+
+use std::fs::File;
+use std::io::BufWriter;
+use std::path::Path;
+
+use image::{
+    Delay, Frame, ImageResult, Rgba, RgbaImage,
+    codecs::gif::{GifEncoder, Repeat},
+};
+
+use crate::game_state::GameState;
+
+const GROUND_COLOR: Rgba<u8> = Rgba([0, 140, 0, 255]);
+const WALL_COLOR: Rgba<u8> = Rgba([0, 0, 0, 255]);
+const AGENT_COLOR: Rgba<u8> = Rgba([255, 0, 0, 255]);
+const GHOST_COLOR: Rgba<u8> = Rgba([255, 255, 255, 255]);
+const GOBLET_COLOR: Rgba<u8> = Rgba([255, 215, 0, 255]);
+const FALSE_GOBLET_COLOR: Rgba<u8> = Rgba([255, 69, 0, 255]);
+const BANNER_COLOR: Rgba<u8> = Rgba([0, 0, 0, 255]);
+const TEXT_COLOR: Rgba<u8> = Rgba([255, 255, 255, 255]);
+
+const BANNER_PADDING: u32 = 4;
+
+/// Renders a top-down view of `state`, matching the colors used by the Bevy scene,
+/// with `banner` drawn as lines of text above the board.
+pub fn render_frame(state: &GameState, cell_pixels: u32, banner: &[String]) -> RgbaImage {
+    let board = &state.board;
+    let board_width = board.width as u32 * cell_pixels;
+    let board_height = board.height as u32 * cell_pixels;
+
+    let longest_line = banner.iter().map(|l| l.chars().count()).max().unwrap_or(0) as u32;
+    let text_scale =
+        if longest_line * (font::GLYPH_WIDTH + 1) * 2 + 2 * BANNER_PADDING <= board_width {
+            2
+        } else {
+            1
+        };
+    let line_height = (font::GLYPH_HEIGHT + 2) * text_scale;
+    let banner_height = if banner.is_empty() {
+        0
+    } else {
+        banner.len() as u32 * line_height + 2 * BANNER_PADDING
+    };
+
+    let mut image = RgbaImage::from_pixel(board_width, board_height + banner_height, GROUND_COLOR);
+
+    fill_rect(&mut image, 0, 0, board_width, banner_height, BANNER_COLOR);
+    for (i, line) in banner.iter().enumerate() {
+        draw_text(
+            &mut image,
+            BANNER_PADDING,
+            BANNER_PADDING + i as u32 * line_height,
+            line,
+            text_scale,
+        );
+    }
+
+    let cell_origin = |(x, y): (usize, usize)| {
+        (
+            x as u32 * cell_pixels,
+            banner_height + y as u32 * cell_pixels,
+        )
+    };
+
+    for &position in &board.wall_positions {
+        let (x, y) = cell_origin(position);
+        fill_rect(&mut image, x, y, cell_pixels, cell_pixels, WALL_COLOR);
+    }
+
+    for goblet in &board.goblets {
+        let (x, y) = cell_origin(goblet.position);
+        let color = if goblet.reward > 0 {
+            GOBLET_COLOR
+        } else {
+            FALSE_GOBLET_COLOR
+        };
+        fill_disc(&mut image, x, y, cell_pixels, color);
+    }
+
+    let inset = cell_pixels / 8;
+    let (x, y) = cell_origin(board.agent_position);
+    fill_rect(
+        &mut image,
+        x + inset,
+        y + inset,
+        cell_pixels - 2 * inset,
+        cell_pixels - 2 * inset,
+        AGENT_COLOR,
+    );
+
+    // The ghost floats above the walls in the 3D scene, so it is drawn last.
+    if let Some(ghost_position) = board.ghost_position {
+        let (x, y) = cell_origin(ghost_position);
+        fill_rect(
+            &mut image,
+            x + inset,
+            y + inset,
+            cell_pixels - 2 * inset,
+            cell_pixels - 2 * inset,
+            GHOST_COLOR,
+        );
+    }
+
+    image
+}
+
+/// Writes `frames` to `path`. A `.gif` extension produces an animated GIF that shows each
+/// frame for `delay_secs`; any other path is treated as a directory that receives a
+/// numbered PNG sequence (`frame_0000.png`, `frame_0001.png`, ...).
+pub fn write_frames(frames: Vec<RgbaImage>, path: &Path, delay_secs: f32) -> ImageResult<()> {
+    let is_gif = path
+        .extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("gif"));
+
+    if is_gif {
+        write_gif(frames, path, delay_secs)
+    } else {
+        write_png_sequence(&frames, path)
+    }
+}
+
+fn write_gif(frames: Vec<RgbaImage>, path: &Path, delay_secs: f32) -> ImageResult<()> {
+    let writer = BufWriter::new(File::create(path)?);
+    let mut encoder = GifEncoder::new(writer);
+    encoder.set_repeat(Repeat::Infinite)?;
+
+    let delay = Delay::from_numer_denom_ms((delay_secs.max(0.0) * 1000.0).round() as u32, 1);
+    encoder.encode_frames(
+        frames
+            .into_iter()
+            .map(|frame| Frame::from_parts(frame, 0, 0, delay)),
+    )
+}
+
+fn write_png_sequence(frames: &[RgbaImage], dir: &Path) -> ImageResult<()> {
+    std::fs::create_dir_all(dir)?;
+    for (i, frame) in frames.iter().enumerate() {
+        frame.save(dir.join(format!("frame_{:04}.png", i)))?;
+    }
+    Ok(())
+}
+
+fn fill_rect(image: &mut RgbaImage, x: u32, y: u32, width: u32, height: u32, color: Rgba<u8>) {
+    for py in y..(y + height).min(image.height()) {
+        for px in x..(x + width).min(image.width()) {
+            image.put_pixel(px, py, color);
+        }
+    }
+}
+
+fn fill_disc(image: &mut RgbaImage, x: u32, y: u32, size: u32, color: Rgba<u8>) {
+    let radius = size as f32 / 2.0;
+    for dy in 0..size {
+        for dx in 0..size {
+            let ox = dx as f32 + 0.5 - radius;
+            let oy = dy as f32 + 0.5 - radius;
+            if ox * ox + oy * oy <= radius * radius
+                && x + dx < image.width()
+                && y + dy < image.height()
+            {
+                image.put_pixel(x + dx, y + dy, color);
+            }
+        }
+    }
+}
+
+fn draw_text(image: &mut RgbaImage, x: u32, y: u32, text: &str, scale: u32) {
+    for (i, c) in text.chars().enumerate() {
+        let glyph_x = x + i as u32 * (font::GLYPH_WIDTH + 1) * scale;
+        for (row, bits) in font::glyph(c).iter().enumerate() {
+            for col in 0..font::GLYPH_WIDTH {
+                if bits & (1 << (font::GLYPH_WIDTH - 1 - col)) != 0 {
+                    fill_rect(
+                        image,
+                        glyph_x + col * scale,
+                        y + row as u32 * scale,
+                        scale,
+                        scale,
+                        TEXT_COLOR,
+                    );
+                }
+            }
+        }
+    }
+}
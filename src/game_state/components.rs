@@ -1,16 +1,20 @@
 use crate::{
-    agent::{Action, GhostPolicy},
+    agent::{Action, GhostPolicy, Lane},
     core::GGConfig,
+    error::{GGError, GGResult},
+    pickle, scenario, scene,
 };
 use bevy::prelude::*;
 use bevy_prng::WyRand;
 use pyo3::prelude::*;
 use pyo3_stub_gen::derive::{gen_stub_pyclass, gen_stub_pyclass_complex_enum, gen_stub_pymethods};
 use rand::{
-    Rng, SeedableRng,
+    Rng, RngCore, SeedableRng,
     seq::{IndexedRandom, IteratorRandom},
 };
-use std::collections::HashSet;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet, VecDeque, hash_map::Entry};
+use std::hash::{DefaultHasher, Hash, Hasher};
 
 #[derive(Component)]
 pub struct HoverBox;
@@ -18,6 +22,25 @@ pub struct HoverBox;
 #[derive(Component)]
 pub struct HoverBoxText;
 
+#[derive(Component)]
+pub struct EpisodeOverlay;
+
+#[derive(Component)]
+pub struct EpisodeOverlayText;
+
+/// Sent by the board editor, or by a viewer opened from Python, with the board that replaces
+/// the current one.
+#[derive(Debug, Clone, Message)]
+pub struct BoardEditedMessage {
+    pub board: Board,
+    /// The exact state to show on `board`, or `None` to start a new episode on it.
+    pub state: Option<GameState>,
+}
+
+/// The running tally of one lane, shown at the bottom of that lane's half of the window.
+#[derive(Component)]
+pub struct EpisodeTallyText(pub Lane);
+
 #[derive(Resource, Default, Debug, Clone, Copy)]
 pub struct HoverCell {
     pub cell: Option<UVec2>, // (col, row)
@@ -26,7 +49,7 @@ pub struct HoverCell {
 
 #[gen_stub_pyclass_complex_enum]
 #[pyclass(name = "EntityType")]
-#[derive(Debug, Clone, Component, Reflect)]
+#[derive(Debug, Clone, Component, Reflect, Serialize, Deserialize)]
 pub enum EntityType {
     Empty(),
     Wall(),
@@ -35,7 +58,25 @@ pub enum EntityType {
     Ghost(),
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[gen_stub_pymethods]
+#[pymethods]
+impl EntityType {
+    /// Serializes this cell's entity to JSON.
+    fn to_json(&self) -> GGResult<String> {
+        pickle::to_json(self)
+    }
+
+    #[staticmethod]
+    fn from_json(json: &str) -> GGResult<Self> {
+        pickle::from_json(json)
+    }
+
+    fn __reduce__(&self, py: Python) -> PyResult<(Py<PyAny>, Py<PyAny>)> {
+        pickle::reduce(py, "EntityType", self)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum Agent {
     Player,
     Ghost,
@@ -45,9 +86,101 @@ pub enum Agent {
 #[reflect(Resource)]
 pub struct VisualizePolicy(pub bool);
 
+/// Longest trail drawn behind the agent, in cells.
+pub const TRAIL_LENGTH: usize = 32;
+
+/// How often the agent has been seen on each cell, and the cells it moved through most
+/// recently, oldest first.
+#[derive(Resource, Debug, Clone, Default)]
+pub struct Visitation {
+    pub width: usize,
+    pub height: usize,
+    pub counts: Vec<u32>,
+    pub trail: VecDeque<(usize, usize)>,
+}
+
+impl Visitation {
+    pub fn new(width: usize, height: usize) -> Self {
+        Self {
+            width,
+            height,
+            counts: vec![0; width * height],
+            trail: VecDeque::new(),
+        }
+    }
+
+    /// Counts the agent cell of every state, e.g. of a headless rollout.
+    pub fn from_states(states: &[GameState]) -> Self {
+        let mut visitation = Self::default();
+        for state in states {
+            visitation.record(state);
+        }
+        visitation
+    }
+
+    /// Counts the agent cell of `state`. The trail restarts when the agent jumps, as it does
+    /// when an episode is reset or rewound.
+    pub fn record(&mut self, state: &GameState) {
+        let board = &state.board;
+        if (self.width, self.height) != (board.width, board.height) {
+            *self = Self::new(board.width, board.height);
+        }
+
+        let position = board.agent_position;
+        self.counts[position.0 + position.1 * self.width] += 1;
+
+        match self.trail.back() {
+            Some(&last) if last == position => {}
+            Some(&last) if last.0.abs_diff(position.0) + last.1.abs_diff(position.1) == 1 => {
+                self.trail.push_back(position);
+            }
+            _ => {
+                self.trail.clear();
+                self.trail.push_back(position);
+            }
+        }
+        if self.trail.len() > TRAIL_LENGTH {
+            self.trail.pop_front();
+        }
+    }
+
+    pub fn count(&self, position: (usize, usize)) -> u32 {
+        self.counts[position.0 + position.1 * self.width]
+    }
+
+    pub fn max_count(&self) -> u32 {
+        self.counts.iter().copied().max().unwrap_or(0)
+    }
+}
+
+/// Which visitation layer is drawn over the ground.
+#[derive(Resource, Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum VisitationView {
+    #[default]
+    Off,
+    Trail,
+    Heatmap,
+}
+
+impl VisitationView {
+    pub fn next(self) -> Self {
+        match self {
+            VisitationView::Off => VisitationView::Trail,
+            VisitationView::Trail => VisitationView::Heatmap,
+            VisitationView::Heatmap => VisitationView::Off,
+        }
+    }
+}
+
+/// A tile of the visitation heatmap, tinted by how often the agent stood on `position`.
+#[derive(Component)]
+pub struct HeatmapCell {
+    pub position: (usize, usize),
+}
+
 #[gen_stub_pyclass]
 #[pyclass(name = "GameState")]
-#[derive(Debug, Clone, Resource)]
+#[derive(Debug, Clone, Resource, Serialize, Deserialize)]
 pub struct GameState {
     #[pyo3(get)]
     pub board: Board,
@@ -56,6 +189,11 @@ pub struct GameState {
     #[pyo3(get)]
     pub done: bool,
     pub active_player: Agent,
+    /// The direction the agent actually moved in on the last step, which differs from the
+    /// chosen action when it slipped. `None` before the first step.
+    #[pyo3(get)]
+    #[serde(default)]
+    pub executed_action: Option<Action>,
 
     pub initial_board: Box<Board>,
     pub rng: WyRand,
@@ -66,31 +204,24 @@ pub struct GameState {
 #[gen_stub_pymethods]
 #[pymethods]
 impl GameState {
-    fn all_states(&self) -> Vec<GameState> {
-        let (width, height) = (self.board.width, self.board.height);
-        let mut states = Vec::new();
-
-        for x in 0..width {
-            for y in 0..height {
-                let mut new_board = self.board.clone();
-                new_board.agent_position = (x, y);
-                let new_state = GameState::from(new_board);
-                states.push(new_state);
-            }
-        }
-
-        if self.board.ghost_position.is_some() {
-            for x in 0..width {
-                for y in 0..height {
-                    let mut new_board = self.board.clone();
-                    new_board.ghost_position = Some((x, y));
-                    let new_state = GameState::from(new_board);
-                    states.push(new_state);
-                }
-            }
+    /// Every state on this board in `state_index` order, including terminal ones. With
+    /// `reachable_only`, only the states reachable from the start of the episode under the
+    /// transition model are kept. States share this state's config, initial board and seeds.
+    #[pyo3(signature = (reachable_only=false))]
+    pub fn all_states(&self, reachable_only: bool) -> GGResult<Vec<GameState>> {
+        if reachable_only {
+            let mut states = self
+                .reachable_states()
+                .into_iter()
+                .map(|state| Ok((state.state_index()?, state)))
+                .collect::<GGResult<Vec<_>>>()?;
+            states.sort_by_key(|(index, _)| *index);
+            return Ok(states.into_iter().map(|(_, state)| state).collect());
         }
 
-        states
+        (0..self.num_states())
+            .map(|index| self.from_index(index))
+            .collect()
     }
 
     fn next_state(&self, action: Action) -> GameState {
@@ -105,13 +236,36 @@ impl GameState {
         new_state
     }
 
+    /// Snapshot of this state's random stream, to be restored with `set_rng_state`. The
+    /// snapshot is opaque but can be stored or sent between processes.
+    pub fn get_rng_state(&self) -> GGResult<String> {
+        pickle::to_json(&self.rng)
+    }
+
+    /// Rewinds or advances this state's random stream to a snapshot from `get_rng_state`, so
+    /// the following steps draw the same random numbers as they did after the snapshot.
+    pub fn set_rng_state(&mut self, state: &str) -> GGResult<()> {
+        self.rng = pickle::from_json(state)?;
+        Ok(())
+    }
+
+    /// Returns a copy of this state with its own random stream, derived from the current
+    /// position of this state's stream and `seed_offset`. Forking twice with the same offset
+    /// gives the same stream, different offsets give independent ones, and this state's own
+    /// stream is left untouched.
+    pub fn fork(&self, seed_offset: u64) -> GameState {
+        let position = self.rng.clone().next_u64();
+        let seed = splitmix64(position ^ splitmix64(seed_offset));
+        self.with_seed(seed)
+    }
+
     pub fn step(&mut self, action: Action) -> GameState {
         let state = self.transition(action);
         assert_eq!(state.active_player, Agent::Player);
         state
     }
 
-    fn reset(&self) -> (GameState, u64) {
+    pub fn reset(&self) -> (GameState, u64) {
         let state = GameState::from((*self.initial_board).clone())
             .with_initial_board(&self.initial_board)
             .with_config(&self.config);
@@ -127,6 +281,143 @@ impl GameState {
         (state, seed)
     }
 
+    /// States that can follow this one with non-zero probability: the agent may slip in any
+    /// direction, then the ghost makes every move its policy allows. Terminal states have none.
+    pub fn successors(&self) -> Vec<GameState> {
+        if self.done {
+            return Vec::new();
+        }
+
+        let mut successors = Vec::new();
+        for agent_action in Action::ALL {
+            let state = self.successor(self.board.transition_det(agent_action, Agent::Player));
+            if state.done {
+                successors.push(state);
+                continue;
+            }
+
+            let ghost_actions = match self.config.agent.ghost_policy {
+                Some(GhostPolicy::Random) => Action::ALL.to_vec(),
+                Some(GhostPolicy::Chaser) => self.board.chaser_action().into_iter().collect(),
+                None => Vec::new(),
+            };
+
+            if ghost_actions.is_empty() {
+                successors.push(state);
+                continue;
+            }
+            for ghost_action in ghost_actions {
+                successors
+                    .push(self.successor(state.board.transition_det(ghost_action, Agent::Ghost)));
+            }
+        }
+
+        successors
+    }
+
+    /// Number of distinct states on this board: every non-wall cell for the agent, times every
+    /// cell for the ghost when there is one (the ghost flies over walls). Goblets stay put
+    /// during an episode, where landing on one ends it, so the only goblet status episodes
+    /// reach is every goblet of the initial board in place, and it adds no states of its own.
+    pub fn num_states(&self) -> usize {
+        let agent_cells = self.board.free_cells().len();
+        match self.board.ghost_position {
+            Some(_) => agent_cells * self.board.width * self.board.height,
+            None => agent_cells,
+        }
+    }
+
+    /// Index of this state in `0..num_states()`. The agent's rank among the non-wall cells in
+    /// row-major order is the major index and, with a ghost, the ghost cell `x + y * width` is
+    /// the minor index. States whose goblets differ from their initial board's cannot occur in
+    /// an episode and have no index. `from_index` is the inverse.
+    pub fn state_index(&self) -> GGResult<usize> {
+        let agent_rank = self
+            .board
+            .free_cells()
+            .iter()
+            .position(|&cell| cell == self.board.agent_position)
+            .ok_or_else(|| {
+                GGError::InvalidState(format!(
+                    "agent is inside the wall at {:?}",
+                    self.board.agent_position
+                ))
+            })?;
+
+        if self.board.goblets != self.initial_board.goblets {
+            return Err(GGError::InvalidState(
+                "goblets differ from the initial board's, which no episode reaches".to_string(),
+            ));
+        }
+
+        Ok(match self.board.ghost_position {
+            Some((x, y)) => {
+                agent_rank * self.board.width * self.board.height + x + y * self.board.width
+            }
+            None => agent_rank,
+        })
+    }
+
+    /// Returns the state with index `index` on this board, keeping this state's config, initial
+    /// board and random stream.
+    #[allow(clippy::wrong_self_convention)]
+    pub fn from_index(&self, index: usize) -> GGResult<GameState> {
+        let num_states = self.num_states();
+        if index >= num_states {
+            return Err(GGError::StateIndex { index, num_states });
+        }
+
+        let (width, cells) = (self.board.width, self.board.width * self.board.height);
+        let (agent_rank, ghost_position) = match self.board.ghost_position {
+            Some(_) => (
+                index / cells,
+                Some((index % cells % width, index % cells / width)),
+            ),
+            None => (index, None),
+        };
+
+        let mut board = self.board.clone();
+        board.agent_position = board.free_cells()[agent_rank];
+        board.ghost_position = ghost_position;
+        board.goblets = self.initial_board.goblets.clone();
+
+        Ok(self.successor(board))
+    }
+
+    /// Serializes this state to JSON, its config, initial board and random stream included,
+    /// so a restored state continues the same episode.
+    fn to_json(&self) -> GGResult<String> {
+        pickle::to_json(self)
+    }
+
+    #[staticmethod]
+    fn from_json(json: &str) -> GGResult<Self> {
+        pickle::from_json(json)
+    }
+
+    fn __reduce__(&self, py: Python) -> PyResult<(Py<PyAny>, Py<PyAny>)> {
+        pickle::reduce(py, "GameState", self)
+    }
+
+    fn __getstate__(&self) -> GGResult<String> {
+        pickle::to_json(self)
+    }
+
+    fn __setstate__(&mut self, state: &str) -> GGResult<()> {
+        *self = pickle::from_json(state)?;
+        Ok(())
+    }
+
+    fn __hash__(&self) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        self.hash(&mut hasher);
+        hasher.finish()
+    }
+
+    fn __eq__(&self, other: &Self) -> bool {
+        self == other
+    }
+
     fn __repr__(&self) -> PyResult<String> {
         Ok(format!("GameState({})", self.__str__()?))
     }
@@ -139,6 +430,30 @@ impl GameState {
     }
 }
 
+/// SplitMix64 finalizer, used to spread nearby seeds across unrelated random streams.
+fn splitmix64(value: u64) -> u64 {
+    let mut z = value.wrapping_add(0x9E37_79B9_7F4A_7C15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
+}
+
+/// States are equal when their boards are, regardless of config or random stream.
+impl PartialEq for GameState {
+    fn eq(&self, other: &Self) -> bool {
+        self.board == other.board
+    }
+}
+
+impl Eq for GameState {}
+
+impl Hash for GameState {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.board.agent_position.hash(state);
+        self.board.ghost_position.hash(state);
+    }
+}
+
 impl From<Board> for GameState {
     fn from(board: Board) -> Self {
         let reward = if let Some(ghost_position) = board.ghost_position
@@ -158,16 +473,17 @@ impl From<Board> for GameState {
 
         let done = reward != 0;
 
-        let seed = rand::random::<u32>();
+        let seed = u64::from(rand::random::<u32>());
 
         Self {
             board: board.clone(),
             reward,
             done,
             active_player: Agent::Player,
+            executed_action: None,
             initial_board: Box::new(board),
-            rng: WyRand::default(),
-            rng_seed: seed.into(),
+            rng: WyRand::from_seed(seed.to_ne_bytes()),
+            rng_seed: seed,
             config: GGConfig::default(),
         }
     }
@@ -184,13 +500,13 @@ impl GameState {
             return self.clone();
         }
 
+        let executed_action = Board::slip(&mut self.rng, action, self.active_player, &self.config);
         let board = self
             .board
-            .transition(&mut self.rng, action, self.active_player, &self.config);
+            .transition_det(executed_action, self.active_player);
 
-        let state = GameState::from(board)
-            .with_initial_board(&self.initial_board)
-            .with_config(&self.config);
+        let mut state = self.successor(board);
+        state.executed_action = Some(executed_action);
 
         if state.done {
             return state;
@@ -203,24 +519,10 @@ impl GameState {
                     .choose(&mut self.rng)
                     .expect("Should have at least one action")
             }
-            Some(GhostPolicy::Chaser) => {
-                let ghost_pos = self
-                    .board
-                    .ghost_position
-                    .expect("Ghost position should be present");
-                let agent_pos = self.board.agent_position;
-
-                let dx = agent_pos.0 as isize - ghost_pos.0 as isize;
-                let dy = agent_pos.1 as isize - ghost_pos.1 as isize;
-
-                if dx.abs() > dy.abs() {
-                    if dx > 0 { Action::Right } else { Action::Left }
-                } else if dy > 0 {
-                    Action::Down
-                } else {
-                    Action::Up
-                }
-            }
+            Some(GhostPolicy::Chaser) => self
+                .board
+                .chaser_action()
+                .expect("Ghost position should be present"),
             None => {
                 assert!(self.board.ghost_position.is_none());
                 return state;
@@ -229,16 +531,54 @@ impl GameState {
 
         let board = state.board.transition_det(ghost_action, Agent::Ghost);
 
-        GameState::from(board)
+        let mut state = self.successor(board);
+        state.executed_action = Some(executed_action);
+        state
+    }
+
+    /// States reachable from the start of the episode, found by breadth-first search over
+    /// `successors`.
+    fn reachable_states(&self) -> HashSet<GameState> {
+        let start = self.successor((*self.initial_board).clone());
+        let mut visited = HashSet::from([start.clone()]);
+        let mut queue = VecDeque::from([start]);
+
+        while let Some(state) = queue.pop_front() {
+            for next in state.successors() {
+                if visited.insert(next.clone()) {
+                    queue.push_back(next);
+                }
+            }
+        }
+
+        visited
+    }
+
+    /// The reward as a number that can be summed and averaged: a ghost capture, stored as
+    /// `i32::MIN`, counts as losing the largest goblet reward.
+    pub fn scored_reward(&self) -> f64 {
+        if self.reward == i32::MIN {
+            -(self.config.goblets.max_reward as f64)
+        } else {
+            self.reward as f64
+        }
+    }
+
+    /// Builds the state that follows this one on `board`, continuing this episode's random stream.
+    fn successor(&self, board: Board) -> Self {
+        let mut state = GameState::from(board)
             .with_initial_board(&self.initial_board)
-            .with_config(&self.config)
+            .with_config(&self.config);
+        state.rng = self.rng.clone();
+        state.rng_seed = self.rng_seed;
+        state
     }
 
     pub fn with_config(mut self, config: &GGConfig) -> Self {
         self.config = config.clone();
 
         if let Some(seed) = config.episode_seed {
-            self.with_seed(seed as u64);
+            self = self.with_seed(seed as u64);
         }
 
         self
@@ -247,7 +587,7 @@ impl GameState {
 
 #[gen_stub_pyclass]
 #[pyclass(name = "Goblet")]
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct Goblet {
     #[pyo3(get)]
     pub position: (usize, usize),
@@ -255,9 +595,36 @@ pub struct Goblet {
     pub reward: i32,
 }
 
+#[gen_stub_pymethods]
+#[pymethods]
+impl Goblet {
+    /// Serializes the goblet's position and reward to JSON.
+    fn to_json(&self) -> GGResult<String> {
+        pickle::to_json(self)
+    }
+
+    #[staticmethod]
+    fn from_json(json: &str) -> GGResult<Self> {
+        pickle::from_json(json)
+    }
+
+    fn __reduce__(&self, py: Python) -> PyResult<(Py<PyAny>, Py<PyAny>)> {
+        pickle::reduce(py, "Goblet", self)
+    }
+
+    fn __getstate__(&self) -> GGResult<String> {
+        pickle::to_json(self)
+    }
+
+    fn __setstate__(&mut self, state: &str) -> GGResult<()> {
+        *self = pickle::from_json(state)?;
+        Ok(())
+    }
+}
+
 #[gen_stub_pyclass]
 #[pyclass(name = "Board")]
-#[derive(Debug, Clone, Component)]
+#[derive(Debug, Clone, PartialEq, Eq, Component, Serialize, Deserialize)]
 pub struct Board {
     #[pyo3(get)]
     pub agent_position: (usize, usize),
@@ -297,7 +664,22 @@ fn get_circle_indices(
 }
 
 impl Board {
-    pub fn new(rng: &mut impl Rng, config: &GGConfig) -> Self {
+    /// Generates a board from `rng` with the algorithm `world_generation.generator_version`
+    /// selects. Existing versions must never change what they draw from `rng`; a change to
+    /// generation goes into a new version instead.
+    pub fn new(rng: &mut impl Rng, config: &GGConfig) -> GGResult<Self> {
+        match config.world_generation.generator_version {
+            1 | 2 => Board::generate(rng, config),
+            version => Err(GGError::BoardGeneration(format!(
+                "unknown generator_version {}; the latest is {}",
+                version,
+                scene::LATEST_GENERATOR_VERSION
+            ))),
+        }
+    }
+
+    /// Generation shared by every version so far; they only differ in how goblets are placed.
+    fn generate(rng: &mut impl Rng, config: &GGConfig) -> GGResult<Self> {
         let width = (config.world_generation.world_width / config.world_generation.cell_size)
             .round() as usize;
         let height = (config.world_generation.world_height / config.world_generation.cell_size)
@@ -330,10 +712,12 @@ impl Board {
             .filter(|pos| !wall_positions.contains(pos))
             .collect::<Vec<_>>();
 
-        let agent_position = free_positions
-            .choose(rng)
-            .cloned()
-            .expect("No free positions available");
+        let agent_position = free_positions.choose(rng).cloned().ok_or_else(|| {
+            GGError::BoardGeneration(
+                "obstacles left no free cell for the agent; try fewer or smaller obstacles"
+                    .to_string(),
+            )
+        })?;
 
         let free_positions = free_positions
             .into_iter()
@@ -341,12 +725,12 @@ impl Board {
             .collect::<Vec<_>>();
 
         let ghost_position = if config.agent.ghost_policy.is_some() {
-            Some(
-                free_positions
-                    .choose(rng)
-                    .cloned()
-                    .expect("No free positions available for ghost"),
-            )
+            Some(free_positions.choose(rng).cloned().ok_or_else(|| {
+                GGError::BoardGeneration(
+                    "obstacles left no free cell for the ghost; try fewer or smaller obstacles"
+                        .to_string(),
+                )
+            })?)
         } else {
             None
         };
@@ -356,24 +740,123 @@ impl Board {
             .filter(|&pos| Some(pos) != ghost_position)
             .collect::<Vec<_>>();
 
-        let goblets = (0..config.goblets.number)
-            .filter_map(|_| {
-                free_positions.choose(rng).cloned().map(|position| Goblet {
-                    position,
-                    reward: rng.random_range(
-                        -(config.goblets.max_reward as i32)..=(config.goblets.max_reward as i32),
-                    ),
+        let max_reward = config.goblets.max_reward as i32;
+        let goblets = match config.world_generation.generator_version {
+            // Version 1 draws every goblet from all free cells, so goblets can share a cell and
+            // such boards fail `check_level`. It is kept as is so that cited seeds still work.
+            1 => (0..config.goblets.number)
+                .filter_map(|_| {
+                    free_positions.choose(rng).cloned().map(|position| Goblet {
+                        position,
+                        reward: rng.random_range(-max_reward..=max_reward),
+                    })
                 })
-            })
-            .collect::<Vec<_>>();
+                .collect::<Vec<_>>(),
+            _ => {
+                let mut free_positions = free_positions;
+                let mut goblets = Vec::with_capacity(config.goblets.number);
+                for i in 0..config.goblets.number {
+                    if free_positions.is_empty() {
+                        return Err(GGError::BoardGeneration(format!(
+                            "obstacles left no free cell for goblet {} of {}; try fewer goblets or fewer or smaller obstacles",
+                            i + 1,
+                            config.goblets.number
+                        )));
+                    }
+                    let position =
+                        free_positions.swap_remove(rng.random_range(0..free_positions.len()));
+                    goblets.push(Goblet {
+                        position,
+                        reward: rng.random_range(-max_reward..=max_reward),
+                    });
+                }
+                goblets
+            }
+        };
 
-        Self {
+        Ok(Self {
             agent_position,
             ghost_position,
             goblets,
             wall_positions,
             width,
             height,
+        })
+    }
+
+    /// The board `config` describes: the layout of `config.scenario`, read from `config.level`
+    /// when it names a level file, or generated from `rng` otherwise.
+    pub fn from_config(rng: &mut impl Rng, config: &GGConfig) -> GGResult<Self> {
+        match (&config.scenario, &config.level) {
+            (Some(id), _) => scenario::board(id, config),
+            (None, Some(path)) => Board::load_level(path, config),
+            (None, None) => Board::new(rng, config),
+        }
+    }
+
+    /// Reads a level file holding the JSON of a `Board` and checks that it fits `config`.
+    pub fn load_level(path: &str, config: &GGConfig) -> GGResult<Self> {
+        let json = std::fs::read_to_string(path)
+            .map_err(|e| GGError::Level(format!("failed to read {}: {}", path, e)))?;
+        let board: Board = pickle::from_json(&json)?;
+        board.check_level(config)?;
+        Ok(board)
+    }
+
+    /// Checks that the board has the size `config` describes, that it has a ghost exactly
+    /// when `config` gives the ghost a policy, and that the agent, ghost and goblets are on
+    /// distinct free cells.
+    pub fn check_level(&self, config: &GGConfig) -> GGResult<()> {
+        self.check_shape(config)?;
+
+        let occupants = std::iter::once(("agent", self.agent_position))
+            .chain(self.ghost_position.map(|position| ("ghost", position)))
+            .chain(
+                self.goblets
+                    .iter()
+                    .map(|goblet| ("goblet", goblet.position)),
+            );
+
+        let mut taken = HashSet::new();
+        for (name, position) in occupants {
+            if !self.is_free(position) {
+                return Err(GGError::Level(format!(
+                    "the {} at {:?} is on a wall or off the board",
+                    name, position
+                )));
+            }
+            if !taken.insert(position) {
+                return Err(GGError::Level(format!(
+                    "the {} at {:?} shares its cell with another occupant",
+                    name, position
+                )));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// The part of `check_level` that every state of an episode passes, not just its start:
+    /// the size of the board and whether it has a ghost.
+    pub fn check_shape(&self, config: &GGConfig) -> GGResult<()> {
+        let size = config.world_generation.size();
+        if (self.width, self.height) != size {
+            return Err(GGError::Level(format!(
+                "the level is {}x{} cells but world_generation describes {}x{}",
+                self.width, self.height, size.0, size.1
+            )));
+        }
+
+        match (&config.agent.ghost_policy, self.ghost_position) {
+            (Some(policy), None) => Err(GGError::Level(format!(
+                "agent.ghost_policy is {:?} but the level has no ghost",
+                policy
+            ))),
+            (None, Some(position)) => Err(GGError::Level(format!(
+                "the level has a ghost at {:?} but agent.ghost_policy is None",
+                position
+            ))),
+            _ => Ok(()),
         }
     }
 
@@ -384,6 +867,20 @@ impl Board {
         active_player: Agent,
         config: &GGConfig,
     ) -> Board {
+        self.transition_det(
+            Self::slip(rng, action, active_player, config),
+            active_player,
+        )
+    }
+
+    /// Samples the direction `active_player` actually moves in when it tries `action`. The
+    /// agent slips according to `agent.transition`; the ghost always moves as intended.
+    pub fn slip(
+        rng: &mut impl Rng,
+        action: Action,
+        active_player: Agent,
+        config: &GGConfig,
+    ) -> Action {
         const ACTIONS: [Action; 4] = [Action::Up, Action::Right, Action::Down, Action::Left];
         let rotated_actions = match action {
             Action::Up => ACTIONS,
@@ -403,51 +900,39 @@ impl Board {
             .choose_weighted(rng, |&(idx, _)| weights[idx])
             .expect("Should have at least one movement option");
 
-        self.transition_det(**chosen_action, active_player)
+        **chosen_action
     }
 
-    pub fn transition_det(&self, action: Action, active_player: Agent) -> Self {
-        let (dx, dy) = match action {
-            Action::Up => (0, -1),
-            Action::Right => (1, 0),
-            Action::Down => (0, 1),
-            Action::Left => (-1, 0),
-        };
+    /// The move a chasing ghost makes: one step along the axis on which it is furthest from
+    /// the agent. `None` when there is no ghost.
+    pub fn chaser_action(&self) -> Option<Action> {
+        let ghost_pos = self.ghost_position?;
+        let agent_pos = self.agent_position;
+
+        let dx = agent_pos.0 as isize - ghost_pos.0 as isize;
+        let dy = agent_pos.1 as isize - ghost_pos.1 as isize;
+
+        Some(if dx.abs() > dy.abs() {
+            if dx > 0 { Action::Right } else { Action::Left }
+        } else if dy > 0 {
+            Action::Down
+        } else {
+            Action::Up
+        })
+    }
 
+    pub fn transition_det(&self, action: Action, active_player: Agent) -> Self {
         let mut board = self.clone();
         match active_player {
             Agent::Player => {
-                let new_x = board
-                    .agent_position
-                    .0
-                    .saturating_add_signed(dx)
-                    .clamp(0, board.width - 1);
-
-                let new_y = board
-                    .agent_position
-                    .1
-                    .saturating_add_signed(dy)
-                    .clamp(0, board.height - 1);
-
-                let new_position = (new_x, new_y);
+                let new_position = self.moved(self.agent_position, action);
                 if !board.wall_positions.contains(&new_position) {
                     board.agent_position = new_position;
                 }
             }
             Agent::Ghost => {
                 if let Some(ghost_pos) = board.ghost_position {
-                    let new_x: usize = ghost_pos
-                        .0
-                        .saturating_add_signed(dx)
-                        .clamp(0, board.width - 1);
-
-                    let new_y = ghost_pos
-                        .1
-                        .saturating_add_signed(dy)
-                        .clamp(0, board.height - 1);
-                    let new_position = (new_x, new_y);
-
-                    board.ghost_position = Some(new_position);
+                    board.ghost_position = Some(self.moved(ghost_pos, action));
                 }
             }
         };
@@ -455,6 +940,39 @@ impl Board {
         board
     }
 
+    /// The cell one step from `position` in the direction of `action`, staying put at the
+    /// edge of the board. Walls are not taken into account.
+    fn moved(&self, position: (usize, usize), action: Action) -> (usize, usize) {
+        let (dx, dy) = match action {
+            Action::Up => (0, -1),
+            Action::Right => (1, 0),
+            Action::Down => (0, 1),
+            Action::Left => (-1, 0),
+        };
+
+        (
+            position
+                .0
+                .saturating_add_signed(dx)
+                .clamp(0, self.width - 1),
+            position
+                .1
+                .saturating_add_signed(dy)
+                .clamp(0, self.height - 1),
+        )
+    }
+
+    fn check_in_bounds(&self, position: (usize, usize)) -> GGResult<()> {
+        if position.0 < self.width && position.1 < self.height {
+            Ok(())
+        } else {
+            Err(GGError::InvalidArgument(format!(
+                "{:?} is outside the {}x{} board",
+                position, self.width, self.height
+            )))
+        }
+    }
+
     pub fn get(&self, position: &(usize, usize)) -> EntityType {
         if self.wall_positions.contains(position) {
             EntityType::Wall()
@@ -476,8 +994,104 @@ impl Board {
 }
 
 #[gen_stub_pymethods]
+#[pymethods]
 impl Board {
     fn __getitem__(&self, position: (usize, usize)) -> EntityType {
         self.get(&position)
     }
+
+    /// Whether the agent may stand on `position`: it is on the board and not a wall.
+    pub fn is_free(&self, position: (usize, usize)) -> bool {
+        position.0 < self.width
+            && position.1 < self.height
+            && !self.wall_positions.contains(&position)
+    }
+
+    /// Cells that are not walls, in row-major order.
+    pub fn free_cells(&self) -> Vec<(usize, usize)> {
+        (0..self.height)
+            .flat_map(|y| (0..self.width).map(move |x| (x, y)))
+            .filter(|cell| !self.wall_positions.contains(cell))
+            .collect()
+    }
+
+    /// Free cells the agent can reach from `position` in one move, in action tag order. Moves
+    /// into a wall or off the edge of the board leave the agent in place and are not listed.
+    pub fn neighbors(&self, position: (usize, usize)) -> GGResult<Vec<(usize, usize)>> {
+        self.check_in_bounds(position)?;
+
+        Ok(Action::ALL
+            .iter()
+            .map(|&action| self.moved(position, action))
+            .filter(|&cell| cell != position && self.is_free(cell))
+            .collect())
+    }
+
+    /// Number of moves the agent needs to get from `start` to `goal` around the walls, or
+    /// `None` when `goal` cannot be reached.
+    pub fn distance(&self, start: (usize, usize), goal: (usize, usize)) -> GGResult<Option<usize>> {
+        Ok(self.shortest_path(start, goal)?.map(|path| path.len() - 1))
+    }
+
+    /// A shortest sequence of free cells from `start` to `goal`, both included, found by
+    /// breadth-first search. `None` when either end is a wall or `goal` cannot be reached.
+    pub fn shortest_path(
+        &self,
+        start: (usize, usize),
+        goal: (usize, usize),
+    ) -> GGResult<Option<Vec<(usize, usize)>>> {
+        self.check_in_bounds(start)?;
+        self.check_in_bounds(goal)?;
+        if !self.is_free(start) || !self.is_free(goal) {
+            return Ok(None);
+        }
+
+        let mut previous = HashMap::from([(start, start)]);
+        let mut queue = VecDeque::from([start]);
+
+        while let Some(cell) = queue.pop_front() {
+            if cell == goal {
+                let mut path = vec![goal];
+                while let Some(&before) = previous.get(path.last().unwrap())
+                    && before != *path.last().unwrap()
+                {
+                    path.push(before);
+                }
+                path.reverse();
+                return Ok(Some(path));
+            }
+
+            for next in self.neighbors(cell)? {
+                if let Entry::Vacant(entry) = previous.entry(next) {
+                    entry.insert(cell);
+                    queue.push_back(next);
+                }
+            }
+        }
+
+        Ok(None)
+    }
+
+    /// Serializes the board's size, walls and occupants to JSON.
+    fn to_json(&self) -> GGResult<String> {
+        pickle::to_json(self)
+    }
+
+    #[staticmethod]
+    fn from_json(json: &str) -> GGResult<Self> {
+        pickle::from_json(json)
+    }
+
+    fn __reduce__(&self, py: Python) -> PyResult<(Py<PyAny>, Py<PyAny>)> {
+        pickle::reduce(py, "Board", self)
+    }
+
+    fn __getstate__(&self) -> GGResult<String> {
+        pickle::to_json(self)
+    }
+
+    fn __setstate__(&mut self, state: &str) -> GGResult<()> {
+        *self = pickle::from_json(state)?;
+        Ok(())
+    }
 }
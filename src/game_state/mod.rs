@@ -1,12 +1,14 @@
 mod components;
 mod systems;
+#[cfg(test)]
+mod tests;
 
 use bevy::input::common_conditions::*;
 use bevy::prelude::*;
 
 pub use components::*;
 
-use crate::core::GGConfig;
+use crate::core::{ComparisonPolicy, GGConfig, StartupSets, UpdateSets};
 
 pub struct GameStatePlugin;
 
@@ -18,10 +20,24 @@ impl Plugin for GameStatePlugin {
         });
 
         app.insert_resource(VisualizePolicy(false));
+        app.init_resource::<Visitation>();
+        app.init_resource::<VisitationView>();
+        app.add_message::<BoardEditedMessage>();
 
         app.add_systems(
             Startup,
-            (systems::setup_hover_box, systems::thicker_gizmos)
+            (
+                systems::setup_hover_box,
+                systems::setup_episode_overlay,
+                systems::thicker_gizmos,
+            )
+                .run_if(|config: Res<GGConfig>| !config.headless),
+        );
+
+        app.add_systems(
+            Startup,
+            systems::setup_heatmap
+                .after(StartupSets::Walls)
                 .run_if(|config: Res<GGConfig>| !config.headless),
         );
 
@@ -29,9 +45,14 @@ impl Plugin for GameStatePlugin {
             Update,
             (
                 systems::update_hover_box,
+                systems::update_episode_overlay,
                 systems::cursor_to_grid_cell,
                 systems::visualize_policy,
+                systems::highlight_disagreements.run_if(resource_exists::<ComparisonPolicy>),
                 systems::toggle_policy_visualization.run_if(input_just_pressed(KeyCode::KeyP)),
+                systems::cycle_visitation_view.run_if(input_just_pressed(KeyCode::KeyH)),
+                systems::respawn_heatmap.after(UpdateSets::EditBoard),
+                (systems::update_heatmap, systems::draw_trail).chain(),
             )
                 .run_if(|config: Res<GGConfig>| !config.headless),
         );
@@ -1,10 +1,12 @@
 use bevy::{prelude::*, window::PrimaryWindow};
 
 use crate::{
-    agent::Action,
-    core::{GGConfig, Policy},
+    agent::{Action, AutoResetCountdown, ComparisonLane, EpisodeTally, Lane},
+    core::{ComparisonPolicy, GGConfig, Policy},
     game_state::{
-        EntityType, GameState, Goblet, HoverBox, HoverBoxText, HoverCell, VisualizePolicy,
+        BoardEditedMessage, EntityType, EpisodeOverlay, EpisodeOverlayText, EpisodeTallyText,
+        GameState, Goblet, HeatmapCell, HoverBox, HoverBoxText, HoverCell, TRAIL_LENGTH,
+        Visitation, VisitationView, VisualizePolicy,
     },
     scene::GroundPlane,
 };
@@ -30,9 +32,6 @@ pub fn cursor_to_grid_cell(
     mut hover: ResMut<HoverCell>,
 ) {
     let Ok(window) = windows.single() else { return };
-    let Ok((camera, cam_transform)) = cams.single() else {
-        return;
-    };
     let Ok(plane_gt) = plane_q.single() else {
         return;
     };
@@ -42,6 +41,16 @@ pub fn cursor_to_grid_cell(
         return;
     };
 
+    // A side-by-side comparison has one camera per half of the window.
+    let Some((camera, cam_transform)) = cams.iter().find(|(camera, _)| {
+        camera
+            .logical_viewport_rect()
+            .is_some_and(|rect| rect.contains(cursor))
+    }) else {
+        *hover = HoverCell::default();
+        return;
+    };
+
     // World ray
     let Ok(ray) = camera.viewport_to_world(cam_transform, cursor) else {
         return;
@@ -206,8 +215,183 @@ pub fn update_hover_box(
         } else {
             0
         },
-        policy.0[(cell.y * (game_state.board.width as u32) + cell.x) as usize]
+        policy.describe(policy.index(&game_state.board, (cell.x as usize, cell.y as usize)))
     );
+
+    if policy.ghost_aware
+        && let Some(ghost_position) = game_state.board.ghost_position
+    {
+        text.0 += &format!("\nGhost At: {:?}", ghost_position);
+    }
+}
+
+pub fn setup_episode_overlay(mut commands: Commands, comparison: Option<Res<ComparisonPolicy>>) {
+    commands
+        .spawn((
+            Node {
+                position_type: PositionType::Absolute,
+                display: Display::None,
+                width: Val::Percent(100.0),
+                top: Val::Percent(40.0),
+                justify_content: JustifyContent::Center,
+                ..default()
+            },
+            GlobalZIndex(5),
+            EpisodeOverlay,
+            Name::new("EpisodeOverlay"),
+        ))
+        .with_children(|p| {
+            p.spawn((
+                Node {
+                    padding: UiRect::all(Val::Px(16.0)),
+                    ..default()
+                },
+                BorderRadius::all(Val::Px(4.0)),
+                BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.75)),
+            ))
+            .with_children(|p| {
+                p.spawn((
+                    Text::new(""),
+                    TextFont {
+                        font_size: 24.0,
+                        ..default()
+                    },
+                    TextLayout::new_with_justify(Justify::Center),
+                    EpisodeOverlayText,
+                ));
+            });
+        });
+
+    let lanes: &[Lane] = if comparison.is_some() {
+        &[Lane::Primary, Lane::Comparison]
+    } else {
+        &[Lane::Primary]
+    };
+
+    for &lane in lanes {
+        let (left, right) = match lane {
+            Lane::Primary => (Val::Px(5.0), Val::Auto),
+            Lane::Comparison => (Val::Auto, Val::Px(5.0)),
+        };
+
+        commands
+            .spawn((
+                Node {
+                    position_type: PositionType::Absolute,
+                    bottom: Val::Px(5.0),
+                    left,
+                    right,
+                    padding: Val::Px(2.5).into(),
+                    ..default()
+                },
+                BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.6)),
+            ))
+            .with_children(|p| {
+                p.spawn((
+                    Text::new(""),
+                    TextFont {
+                        font_size: 14.0,
+                        ..default()
+                    },
+                    EpisodeTallyText(lane),
+                ));
+            });
+    }
+}
+
+/// Shows how the episode ended once `GameState.done` is set, and the running tally of
+/// finished episodes. When comparing, the overlay waits for both lanes to finish and each
+/// half of the window shows the total reward of its policy.
+#[allow(clippy::too_many_arguments)]
+pub fn update_episode_overlay(
+    game_state: Res<GameState>,
+    config: Res<GGConfig>,
+    tally: Res<EpisodeTally>,
+    countdown: Res<AutoResetCountdown>,
+    comparison: Option<Res<ComparisonLane>>,
+    mut q_overlay: Query<&mut Node, With<EpisodeOverlay>>,
+    mut q_text: Query<&mut Text, (With<EpisodeOverlayText>, Without<EpisodeTallyText>)>,
+    mut q_tally: Query<(&mut Text, &EpisodeTallyText), Without<EpisodeOverlayText>>,
+) {
+    for (mut tally_text, &EpisodeTallyText(lane)) in q_tally.iter_mut() {
+        let tally = match (lane, &comparison) {
+            (Lane::Primary, _) => &*tally,
+            (Lane::Comparison, Some(comparison)) => &comparison.tally,
+            (Lane::Comparison, None) => continue,
+        };
+
+        let summary = match tally.average_return() {
+            Some(average) => format!(
+                "Episodes: {} | Average Return: {:.2}",
+                tally.episodes, average
+            ),
+            None => "Episodes: 0".to_string(),
+        };
+        tally_text.0 = match (lane, comparison.is_some()) {
+            (_, false) => summary,
+            (Lane::Primary, true) => {
+                format!(
+                    "Policy | Total Reward: {} | {}",
+                    tally.total_return, summary
+                )
+            }
+            (Lane::Comparison, true) => {
+                format!(
+                    "Comparison | Total Reward: {} | {}",
+                    tally.total_return, summary
+                )
+            }
+        };
+    }
+
+    let (Ok(mut node), Ok(mut text)) = (q_overlay.single_mut(), q_text.single_mut()) else {
+        return;
+    };
+
+    if !game_state.done
+        || comparison
+            .as_ref()
+            .is_some_and(|comparison| !comparison.state.done)
+    {
+        node.display = Display::None;
+        return;
+    }
+
+    // The countdown has not started yet on the frame the episode ends, or while paused before
+    // it first ticks.
+    let next = match config.auto_reset_secs {
+        Some(secs) => format!(
+            "Next episode in {:.1}s",
+            countdown.0.as_ref().map_or(secs, Timer::remaining_secs)
+        ),
+        None => "Press R to restart".to_string(),
+    };
+
+    text.0 = match &comparison {
+        Some(comparison) => format!(
+            "Episode Over\nPolicy: {} ({})\nComparison: {} ({})\n{}",
+            ending(&game_state),
+            game_state.scored_reward(),
+            ending(&comparison.state),
+            comparison.state.scored_reward(),
+            next
+        ),
+        None => format!(
+            "Episode Over\n{}\nFinal Reward: {}\n{}",
+            ending(&game_state),
+            game_state.scored_reward(),
+            next
+        ),
+    };
+    node.display = Display::Flex;
+}
+
+fn ending(state: &GameState) -> String {
+    if state.reward == i32::MIN {
+        "Caught by the ghost".to_string()
+    } else {
+        format!("Found a goblet worth {}", state.reward)
+    }
 }
 
 pub fn thicker_gizmos(mut store: ResMut<GizmoConfigStore>) {
@@ -234,44 +418,230 @@ pub fn visualize_policy(
     let world_width = config.world_generation.world_width;
     let world_height = config.world_generation.world_height;
 
-    for (i, action) in policy.0.iter().enumerate() {
-        let col = (i as u32) % (world_width / cell_size) as u32;
-        let row = (i as u32) / (world_width / cell_size) as u32;
+    let board = &game_state.board;
+    let cells = (0..board.height).flat_map(|row| (0..board.width).map(move |col| (col, row)));
 
-        if game_state
-            .board
-            .wall_positions
-            .contains(&(col as usize, row as usize))
-        {
+    // Ghost-aware policies show the action for every agent cell with the ghost where it is now.
+    for (col, row) in cells {
+        let i = policy.index(board, (col, row));
+        let Some(Some(probabilities)) = policy.probabilities.get(i) else {
+            continue;
+        };
+
+        if board.wall_positions.contains(&(col, row)) {
             continue;
         }
 
-        match game_state.board.get(&(col as usize, row as usize)) {
+        match board.get(&(col, row)) {
             EntityType::Wall() | EntityType::Goblet(_) => continue,
             _ => {}
         }
 
+        let mut center = cell_to_world((col, row), cell_size, world_width, world_height);
+        center.y = 5.0;
+
+        // Deterministic cells get one arrow across the cell; stochastic cells get one arrow
+        // out of the center per action, faded by its probability.
+        let deterministic = policy.deterministic_action(i);
+        for (action, &probability) in Action::ALL.iter().zip(probabilities) {
+            if probability <= 0.0 {
+                continue;
+            }
+
+            let dir = match action {
+                Action::Up => Vec3::new(0.0, 0.0, -1.0),
+                Action::Down => Vec3::new(0.0, 0.0, 1.0),
+                Action::Left => Vec3::new(-1.0, 0.0, 0.0),
+                Action::Right => Vec3::new(1.0, 0.0, 0.0),
+            };
+
+            let arrow_start = if deterministic.is_some() {
+                center - dir * 0.9 * cell_size / 2.0
+            } else {
+                center
+            };
+            let arrow_end = center + dir * 0.9 * cell_size / 2.0;
+            let arrow_length = (arrow_end - arrow_start).length();
+
+            gizmos
+                .arrow(arrow_start, arrow_end, Color::BLACK.with_alpha(probability))
+                .with_tip_length(arrow_length * 0.2);
+        }
+    }
+}
+
+/// Outlines the cells where the two policies of a side-by-side comparison choose different
+/// actions, comparing the most likely action of stochastic cells. Ghost-aware policies are
+/// compared with the ghost where it is in the primary lane.
+pub fn highlight_disagreements(
+    mut gizmos: Gizmos,
+    policy: Res<Policy>,
+    comparison: Res<ComparisonPolicy>,
+    game_state: Res<GameState>,
+    config: Res<GGConfig>,
+) {
+    let cell_size = config.world_generation.cell_size;
+    let board = &game_state.board;
+
+    for position in board.free_cells() {
+        if matches!(board.get(&position), EntityType::Goblet(_)) {
+            continue;
+        }
+
+        let action = policy.greedy(policy.index(board, position));
+        let other = comparison.0.greedy(comparison.0.index(board, position));
+        if action == other {
+            continue;
+        }
+
         let mut center = cell_to_world(
-            (col as usize, row as usize),
+            position,
             cell_size,
-            world_width,
-            world_height,
+            config.world_generation.world_width,
+            config.world_generation.world_height,
         );
-        center.y = 5.0;
+        center.y = 0.55;
+
+        gizmos.rect(
+            Isometry3d::new(center, Quat::from_rotation_x(std::f32::consts::FRAC_PI_2)),
+            Vec2::splat(cell_size * 0.9),
+            Color::srgb(1.0, 0.0, 1.0),
+        );
+    }
+}
+
+pub fn cycle_visitation_view(mut view: ResMut<VisitationView>) {
+    *view = view.next();
+}
+
+/// Spawns one hidden tile per cell just above the ground for the visitation heatmap.
+pub fn setup_heatmap(
+    mut commands: Commands,
+    mut meshes: Option<ResMut<Assets<Mesh>>>,
+    mut materials: Option<ResMut<Assets<StandardMaterial>>>,
+    game_state: Res<GameState>,
+    config: Res<GGConfig>,
+) {
+    let (Some(meshes), Some(materials)) = (&mut meshes, &mut materials) else {
+        return;
+    };
+
+    let cell_size = config.world_generation.cell_size;
+    let mesh = meshes.add(Plane3d::default().mesh().size(cell_size, cell_size));
+    let board = &game_state.board;
+
+    for position in board.free_cells() {
+        let mut center = cell_to_world(
+            position,
+            cell_size,
+            config.world_generation.world_width,
+            config.world_generation.world_height,
+        );
+        // Just above the top of the ground plane, which is one unit thick.
+        center.y = 0.51;
+
+        let material = materials.add(StandardMaterial {
+            base_color: Color::NONE,
+            alpha_mode: AlphaMode::Blend,
+            unlit: true,
+            ..default()
+        });
 
-        let dir = match action {
-            Action::Up => Vec3::new(0.0, 0.0, -1.0),
-            Action::Down => Vec3::new(0.0, 0.0, 1.0),
-            Action::Left => Vec3::new(-1.0, 0.0, 0.0),
-            Action::Right => Vec3::new(1.0, 0.0, 0.0),
+        commands.spawn((
+            Name::new("HeatmapCell"),
+            HeatmapCell { position },
+            Mesh3d(mesh.clone()),
+            MeshMaterial3d(material),
+            Transform::from_translation(center),
+            Visibility::Hidden,
+        ));
+    }
+}
+
+/// Replaces the heatmap tiles of the old board with one per free cell of an edited one.
+pub fn respawn_heatmap(
+    mut messages: MessageReader<BoardEditedMessage>,
+    mut commands: Commands,
+    tiles: Query<Entity, With<HeatmapCell>>,
+) {
+    if messages.read().count() == 0 {
+        return;
+    }
+
+    for entity in tiles.iter() {
+        commands.entity(entity).despawn();
+    }
+    commands.run_system_cached(setup_heatmap);
+}
+
+/// Tints the heatmap tiles by visit count, relative to the most visited cell.
+pub fn update_heatmap(
+    visitation: Res<Visitation>,
+    view: Res<VisitationView>,
+    materials: Option<ResMut<Assets<StandardMaterial>>>,
+    mut tiles: Query<(
+        &HeatmapCell,
+        &MeshMaterial3d<StandardMaterial>,
+        &mut Visibility,
+    )>,
+) {
+    if !visitation.is_changed() && !view.is_changed() {
+        return;
+    }
+    let Some(mut materials) = materials else {
+        return;
+    };
+
+    let max_count = visitation.max_count().max(1) as f32;
+    for (tile, material, mut visibility) in tiles.iter_mut() {
+        let count = if visitation.counts.is_empty() {
+            0
+        } else {
+            visitation.count(tile.position)
         };
 
-        let arrow_start = center - dir * 0.9 * cell_size / 2.0;
-        let arrow_end = center + dir * 0.9 * cell_size / 2.0;
-        let arrow_length = (arrow_end - arrow_start).length();
+        if *view != VisitationView::Heatmap || count == 0 {
+            *visibility = Visibility::Hidden;
+            continue;
+        }
 
-        gizmos
-            .arrow(arrow_start, arrow_end, Color::BLACK)
-            .with_tip_length(arrow_length * 0.2);
+        *visibility = Visibility::Visible;
+        if let Some(material) = materials.get_mut(&material.0) {
+            let heat = count as f32 / max_count;
+            material.base_color = Color::srgba(1.0, 1.0 - heat, 0.0, 0.2 + 0.6 * heat);
+        }
+    }
+}
+
+/// Draws the agent's recent path, fading out towards its oldest cells.
+pub fn draw_trail(
+    mut gizmos: Gizmos,
+    visitation: Res<Visitation>,
+    view: Res<VisitationView>,
+    config: Res<GGConfig>,
+) {
+    if *view != VisitationView::Trail {
+        return;
+    }
+
+    let to_world = |position| {
+        let mut point = cell_to_world(
+            position,
+            config.world_generation.cell_size,
+            config.world_generation.world_width,
+            config.world_generation.world_height,
+        );
+        point.y = 1.0;
+        point
+    };
+
+    let segments = visitation.trail.iter().zip(visitation.trail.iter().skip(1));
+    for (i, (&from, &to)) in segments.enumerate() {
+        let age = (visitation.trail.len() - 2 - i) as f32 / TRAIL_LENGTH as f32;
+        gizmos.line(
+            to_world(from),
+            to_world(to),
+            Color::srgb(1.0, 0.3, 0.3).with_alpha(1.0 - age),
+        );
     }
 }
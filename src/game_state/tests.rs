@@ -0,0 +1,283 @@
+use bevy_prng::WyRand;
+use rand::SeedableRng;
+
+use crate::{
+    agent::GhostPolicy,
+    core::GGConfig,
+    game_state::{Board, GameState},
+    scenario::Scenario,
+    scene,
+};
+
+/// Seeds whose boards are locked for every generator version.
+const GOLDEN_SEEDS: std::ops::Range<u32> = 0..8;
+
+/// Set to rewrite the golden files from the current generator instead of checking them, e.g.
+/// after adding a generator version.
+const UPDATE_ENV: &str = "GG_UPDATE_GOLDEN";
+
+fn golden_configs(version: u32) -> Vec<(&'static str, GGConfig)> {
+    let mut default = GGConfig::default();
+    default.world_generation.generator_version = version;
+
+    let mut ghost = default.clone();
+    ghost.agent.ghost_policy = Some(GhostPolicy::Chaser);
+    ghost.goblets.number = 3;
+    ghost.world_generation.world_width = 60.0;
+    ghost.world_generation.world_height = 40.0;
+    ghost.world_generation.num_obstacles = 3;
+    ghost.world_generation.obstacle_radius_cells = 2;
+
+    vec![("default", default), ("ghost", ghost)]
+}
+
+/// Draws `board` one character per cell, like the scenario layouts, followed by its goblets.
+fn render(board: &Board) -> String {
+    let mut lines = (0..board.height)
+        .map(|y| {
+            (0..board.width)
+                .map(|x| {
+                    let cell = (x, y);
+                    if board.wall_positions.contains(&cell) {
+                        '#'
+                    } else if board.agent_position == cell {
+                        'A'
+                    } else if board.ghost_position == Some(cell) {
+                        'G'
+                    } else if board.goblets.iter().any(|goblet| goblet.position == cell) {
+                        '$'
+                    } else {
+                        '.'
+                    }
+                })
+                .collect::<String>()
+        })
+        .collect::<Vec<_>>();
+
+    lines.push(format!(
+        "goblets: {}",
+        board
+            .goblets
+            .iter()
+            .map(|goblet| format!("{:?} {}", goblet.position, goblet.reward))
+            .collect::<Vec<_>>()
+            .join(", ")
+    ));
+    lines.join("\n")
+}
+
+/// Every golden board of `version`, seeded the way `run` seeds board generation.
+fn generate_all(version: u32) -> String {
+    golden_configs(version)
+        .iter()
+        .flat_map(|(name, config)| {
+            GOLDEN_SEEDS.map(move |seed| {
+                let mut rng = WyRand::from_seed(u64::from(seed).to_ne_bytes());
+                let board = Board::new(&mut rng, config).expect("Golden config should generate");
+                format!(
+                    "config {} generation_seed {}\n{}\n",
+                    name,
+                    seed,
+                    render(&board)
+                )
+            })
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[test]
+fn generated_boards_match_golden_files() {
+    let dir = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("src/game_state/golden");
+
+    for version in 1..=scene::LATEST_GENERATOR_VERSION {
+        let path = dir.join(format!("generator_v{}.txt", version));
+        let boards = generate_all(version);
+
+        if std::env::var_os(UPDATE_ENV).is_some() {
+            std::fs::write(&path, boards).expect("Should write the golden file");
+            continue;
+        }
+
+        let golden = std::fs::read_to_string(&path)
+            .unwrap_or_else(|e| panic!("Failed to read {}: {}", path.display(), e));
+        assert!(
+            boards == golden,
+            "generator_version {} no longer produces the boards in {}. Existing versions must not \
+             change; add a new version instead, or rerun with {}=1 if the change is intended.",
+            version,
+            path.display(),
+            UPDATE_ENV
+        );
+    }
+}
+
+#[test]
+fn unknown_generator_version_is_rejected() {
+    let mut config = GGConfig::default();
+    config.world_generation.generator_version = scene::LATEST_GENERATOR_VERSION + 1;
+
+    assert!(
+        config
+            .validate()
+            .iter()
+            .any(|error| error.field == "world_generation.generator_version")
+    );
+    let mut rng = WyRand::from_seed(0u64.to_ne_bytes());
+    assert!(Board::new(&mut rng, &config).is_err());
+}
+
+#[test]
+fn check_level_requires_a_ghost_exactly_with_a_ghost_policy() {
+    let scenario = Scenario::load("open_room_1goblet@1").expect("Scenario should exist");
+    let mut config = scenario.config;
+    let board = scenario.board;
+    assert!(board.check_level(&config).is_ok());
+
+    let mut with_ghost = board.clone();
+    with_ghost.ghost_position = Some((3, 3));
+    assert!(with_ghost.check_level(&config).is_err());
+
+    config.agent.ghost_policy = Some(GhostPolicy::Chaser);
+    assert!(with_ghost.check_level(&config).is_ok());
+    assert!(board.check_level(&config).is_err());
+}
+
+#[test]
+fn generated_boards_pass_check_level() {
+    for (name, config) in golden_configs(scene::LATEST_GENERATOR_VERSION) {
+        for seed in GOLDEN_SEEDS {
+            let mut rng = WyRand::from_seed(u64::from(seed).to_ne_bytes());
+            let board = Board::new(&mut rng, &config).expect("Golden config should generate");
+            if let Err(e) = board.check_level(&config) {
+                panic!("config {} generation_seed {}: {}", name, seed, e);
+            }
+        }
+    }
+}
+
+/// Version 1 is frozen with its known flaw: it can put two goblets on one cell.
+#[test]
+fn generator_v1_can_stack_goblets() {
+    let (_, config) = golden_configs(1)
+        .into_iter()
+        .find(|(name, _)| *name == "ghost")
+        .expect("Golden configs should include the ghost config");
+    let mut rng = WyRand::from_seed(5u64.to_ne_bytes());
+    let board = Board::new(&mut rng, &config).expect("Golden config should generate");
+
+    assert_eq!(board.goblets[0].position, board.goblets[1].position);
+    assert!(board.check_level(&config).is_err());
+}
+
+#[test]
+fn state_indices_are_dense_and_round_trip() {
+    let scenario = Scenario::load("narrow_corridor_ghost@1").expect("Scenario should exist");
+    let start = GameState::from(scenario.board).with_config(&scenario.config);
+    let states = start.all_states(false).expect("States should be indexable");
+
+    assert_eq!(states.len(), start.num_states());
+    for (index, state) in states.iter().enumerate() {
+        let state_index = state.state_index().expect("State should have an index");
+        assert_eq!(state_index, index);
+        assert_eq!(
+            &start.from_index(state_index).expect("Index is in range"),
+            state
+        );
+    }
+}
+
+#[test]
+fn state_index_only_covers_the_goblets_episodes_reach() {
+    let scenario = Scenario::load("bridge_crossing@1").expect("Scenario should exist");
+    let start = GameState::from(scenario.board).with_config(&scenario.config);
+    assert_eq!(start.num_states(), start.board.free_cells().len());
+
+    let mut taken = start.clone();
+    taken.board.goblets.pop();
+    assert!(taken.state_index().is_err());
+
+    let index = start.state_index().unwrap();
+    assert_eq!(
+        start.from_index(index).unwrap().board.goblets,
+        start.board.goblets
+    );
+}
+
+/// A board drawn like the scenario layouts, with `#` walls and `.` free cells. The agent starts
+/// in the top left free cell and there are no goblets.
+fn hand_built(layout: &[&str]) -> Board {
+    let wall_positions = layout
+        .iter()
+        .enumerate()
+        .flat_map(|(y, row)| {
+            row.chars()
+                .enumerate()
+                .filter(|(_, symbol)| *symbol == '#')
+                .map(move |(x, _)| (x, y))
+        })
+        .collect();
+
+    Board {
+        wall_positions,
+        agent_position: (1, 1),
+        ghost_position: None,
+        goblets: Vec::new(),
+        width: layout[0].len(),
+        height: layout.len(),
+    }
+}
+
+const ROOM_AND_CLOSET: &[&str] = &[
+    "#######", //
+    "#...#.#", //
+    "#.#.#.#", //
+    "#...#.#", //
+    "#######", //
+];
+
+#[test]
+fn neighbors_lists_free_cells_in_action_order() {
+    let board = hand_built(ROOM_AND_CLOSET);
+
+    assert_eq!(board.neighbors((1, 1)).unwrap(), vec![(1, 2), (2, 1)]);
+    assert_eq!(board.neighbors((3, 2)).unwrap(), vec![(3, 1), (3, 3)]);
+    assert!(board.neighbors((7, 0)).is_err());
+}
+
+#[test]
+fn distance_counts_moves_around_walls() {
+    let board = hand_built(ROOM_AND_CLOSET);
+
+    assert_eq!(board.distance((1, 1), (1, 1)).unwrap(), Some(0));
+    assert_eq!(board.distance((1, 1), (3, 1)).unwrap(), Some(2));
+    assert_eq!(board.distance((1, 1), (3, 3)).unwrap(), Some(4));
+    assert_eq!(board.distance((5, 1), (5, 3)).unwrap(), Some(2));
+}
+
+#[test]
+fn distance_is_none_for_walled_off_cells() {
+    let board = hand_built(ROOM_AND_CLOSET);
+
+    assert_eq!(board.distance((1, 1), (5, 2)).unwrap(), None);
+    assert_eq!(board.distance((1, 1), (2, 2)).unwrap(), None);
+    assert_eq!(board.shortest_path((0, 0), (1, 1)).unwrap(), None);
+}
+
+#[test]
+fn shortest_path_includes_both_ends() {
+    let board = hand_built(ROOM_AND_CLOSET);
+
+    assert_eq!(
+        board.shortest_path((1, 1), (1, 1)).unwrap(),
+        Some(vec![(1, 1)])
+    );
+
+    let path = board.shortest_path((1, 1), (3, 3)).unwrap().unwrap();
+    assert_eq!(path.len(), 5);
+    assert_eq!(path.first(), Some(&(1, 1)));
+    assert_eq!(path.last(), Some(&(3, 3)));
+    for step in path.windows(2) {
+        assert!(board.neighbors(step[0]).unwrap().contains(&step[1]));
+    }
+}
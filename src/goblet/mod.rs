@@ -5,10 +5,12 @@ mod visual;
 use bevy::prelude::*;
 use derivative::Derivative;
 use pyo3::prelude::*;
-use pyo3_stub_gen::derive::gen_stub_pyclass;
+use pyo3_stub_gen::derive::{gen_stub_pyclass, gen_stub_pymethods};
 use serde::{Deserialize, Serialize};
 
-use crate::core::{GGConfig, StartupSets};
+use crate::core::{GGConfig, StartupSets, UpdateSets};
+use crate::error::GGResult;
+use crate::pickle;
 
 pub use components::*;
 
@@ -28,8 +30,32 @@ pub struct GobletConfig {
     pub max_reward: u32,
 }
 
+#[gen_stub_pymethods]
 #[pymethods]
 impl GobletConfig {
+    /// Serializes the goblet count and reward bound to JSON.
+    fn to_json(&self) -> GGResult<String> {
+        pickle::to_json(self)
+    }
+
+    #[staticmethod]
+    fn from_json(json: &str) -> GGResult<Self> {
+        pickle::from_json(json)
+    }
+
+    fn __reduce__(&self, py: Python) -> PyResult<(Py<PyAny>, Py<PyAny>)> {
+        pickle::reduce(py, "GobletConfig", self)
+    }
+
+    fn __getstate__(&self) -> GGResult<String> {
+        pickle::to_json(self)
+    }
+
+    fn __setstate__(&mut self, state: &str) -> GGResult<()> {
+        *self = pickle::from_json(state)?;
+        Ok(())
+    }
+
     fn __repr__(&self) -> PyResult<String> {
         Ok(format!("GobletConfig({})", self.__str__()?))
     }
@@ -52,6 +78,10 @@ impl Plugin for GobletPlugin {
             Startup,
             (systems::spawn_goblets).in_set(StartupSets::Goblets),
         );
+        app.add_systems(
+            Update,
+            systems::respawn_goblets.after(UpdateSets::EditBoard),
+        );
     }
 }
 
@@ -1,8 +1,8 @@
 use bevy::prelude::*;
 
 use crate::core::GGConfig;
-use crate::game_state::{GameState, Goblet};
-use crate::goblet::GobletBundle;
+use crate::game_state::{BoardEditedMessage, GameState, Goblet};
+use crate::goblet::{self, GobletBundle};
 
 use super::visual::GobletGraphicsAssets;
 
@@ -52,3 +52,19 @@ pub fn spawn_goblets(
         }
     }
 }
+
+/// Replaces the goblets of the old board with those of an edited one.
+pub fn respawn_goblets(
+    mut messages: MessageReader<BoardEditedMessage>,
+    mut commands: Commands,
+    goblets: Query<Entity, With<goblet::Goblet>>,
+) {
+    if messages.read().count() == 0 {
+        return;
+    }
+
+    for entity in goblets.iter() {
+        commands.entity(entity).despawn();
+    }
+    commands.run_system_cached(spawn_goblets);
+}
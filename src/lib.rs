@@ -2,24 +2,35 @@ mod agent;
 mod camera;
 mod core;
 mod debug;
+mod editor;
+mod error;
+mod export;
 mod game_state;
 mod goblet;
+mod pickle;
+mod planner;
+mod policy;
+mod scenario;
 mod scene;
+mod tabular;
+mod tui;
+mod viewer;
 
 use bevy::prelude::*;
 use bevy::window::PrimaryWindow;
-use bevy::winit::WinitWindows;
+use bevy::winit::{WakeUp, WinitPlugin, WinitWindows};
 use bevy_prng::WyRand;
-use bevy_rand::prelude::*;
-use numpy::{PyArray2, PyArrayMethods};
-use pyo3::exceptions::{PyRuntimeError, PyTypeError};
+use numpy::{PyArray1, PyArray2, PyArrayMethods};
+use pyo3::exceptions::{PyRuntimeError, PyTypeError, PyValueError};
 use pyo3::prelude::*;
 use pyo3_stub_gen::{define_stub_info_gatherer, derive::gen_stub_pyfunction};
 use rand::SeedableRng;
 
 use crate::{
-    core::GGConfig,
+    core::{GGConfig, Renderer},
+    error::GGResult,
     game_state::{Board, GameState},
+    policy::PolicyLayout,
 };
 
 #[gen_stub_pyfunction]
@@ -31,20 +42,36 @@ fn parse_config(config_path: &str) -> PyResult<GGConfig> {
     let config: GGConfig = serde_yaml::from_str(&config_str)
         .map_err(|e| PyRuntimeError::new_err(format!("Failed to parse config file: {}", e)))?;
 
+    config.check()?;
+
     Ok(config)
 }
 
-fn generate_app(mut config: GGConfig, policy: Vec<agent::Action>) -> App {
+fn generate_app(
+    config: GGConfig,
+    game_state: GameState,
+    policy: core::Policy,
+    comparison_policy: Option<core::Policy>,
+    viewer: Option<viewer::ViewerPlugin>,
+) -> App {
     let mut app = App::new();
 
     if !config.headless {
-        app.add_plugins(DefaultPlugins.set(WindowPlugin {
-            primary_window: Some(Window {
-                title: "Ghouls and Goblets".to_string(),
-                ..Default::default()
-            }),
-            ..Default::default()
-        }));
+        // A viewer runs the app off the main thread, which the event loop must allow.
+        let mut winit = WinitPlugin::<WakeUp>::default();
+        winit.run_on_any_thread = viewer.is_some();
+
+        app.add_plugins(
+            DefaultPlugins
+                .set(WindowPlugin {
+                    primary_window: Some(Window {
+                        title: "Ghouls and Goblets".to_string(),
+                        ..Default::default()
+                    }),
+                    ..Default::default()
+                })
+                .set(winit),
+        );
 
         app.add_systems(Update, force_focus);
     }
@@ -53,83 +80,340 @@ fn generate_app(mut config: GGConfig, policy: Vec<agent::Action>) -> App {
         app.add_plugins(debug::DebugPlugin);
     }
 
-    let seed = if let Some(seed) = config.generation_seed {
+    app.add_plugins(core::GGPlugin {
+        config,
+        game_state,
+        policy,
+        comparison_policy,
+    });
+    if let Some(viewer) = viewer {
+        app.add_plugins(viewer);
+    }
+
+    app
+}
+
+/// Builds the state episodes start from, on `board` or else on the board `config` describes,
+/// filling in the seeds `config` leaves unset. This is the only place boards are made for
+/// both Python and the app, so they always agree.
+fn initial_state(config: &mut GGConfig, board: Option<Board>) -> GGResult<(GameState, u32, u64)> {
+    let generation_seed = if let Some(seed) = config.generation_seed {
         seed
     } else {
         let seed = rand::random::<u16>().into();
         config.generation_seed = Some(seed);
         seed
     };
+    let board = match board {
+        Some(board) => {
+            board.check_level(config)?;
+            board
+        }
+        None => {
+            let mut rng = WyRand::from_seed(u64::from(generation_seed).to_ne_bytes());
+            Board::from_config(&mut rng, config)?
+        }
+    };
+    let episode_seed = if let Some(seed) = config.episode_seed {
+        seed
+    } else {
+        let seed = rand::random::<u32>();
+        config.episode_seed = Some(seed);
+        seed
+    };
+    let initial_state = GameState::from(board)
+        .with_config(config)
+        .with_seed(episode_seed.into());
+    let episode_seed = initial_state.rng_seed;
 
-    app.add_plugins((
-        EntropyPlugin::<WyRand>::with_seed(u64::from(seed).to_ne_bytes()),
-        core::GGPlugin {
-            config: config.clone(),
-            policy,
-        },
-    ));
-
-    app
+    Ok((initial_state, generation_seed, episode_seed))
 }
 
+/// Opens the game for `config`, played by `policy`. With `comparison_policy`, the window is
+/// split in two and the second policy plays the same board and episode seed on the right,
+/// with the cells where the policies disagree outlined on both halves. A hand-authored
+/// `board` replaces the one `config` describes; `InvalidConfigError` is raised unless it has
+/// the size `config` describes and a ghost exactly when `config` gives the ghost a policy.
 #[gen_stub_pyfunction]
 #[pyfunction(name = "run")]
-#[pyo3(signature=(config, policy=None))]
+#[pyo3(signature=(config, policy=None, layout=PolicyLayout::WidthHeight, comparison_policy=None, board=None))]
 fn run(
     py: Python<'_>,
     mut config: GGConfig,
     policy: Option<Py<PyAny>>,
+    layout: PolicyLayout,
+    comparison_policy: Option<Py<PyAny>>,
+    board: Option<Board>,
 ) -> PyResult<Option<(GameState, u32, u64)>> {
-    let generation_seed = if let Some(seed) = config.generation_seed {
-        seed
-    } else {
-        let seed = rand::random::<u16>().into();
-        config.generation_seed = Some(seed);
-        seed
-    };
-    let mut rng = WyRand::from_seed(u64::from(generation_seed).to_ne_bytes());
-    let mut initial_state = GameState::from(Board::new(&mut rng, &config)).with_config(&config);
-
-    if let Some(episode_seed) = config.episode_seed {
-        initial_state = initial_state.with_seed(episode_seed.into());
-    } else {
-        config.episode_seed = Some(initial_state.rng_seed as u32);
-    }
-
-    let episode_seed = initial_state.rng_seed;
+    config.check()?;
+    let (initial_state, generation_seed, episode_seed) = initial_state(&mut config, board)?;
 
     if !config.headless {
         let policy_any = policy
             .ok_or_else(|| PyTypeError::new_err("Policy must be provided in non-headless mode"))?;
 
-        let policy = if let Ok(arr_obj) = policy_any.cast_bound::<PyArray2<Py<PyAny>>>(py) {
-            let array = unsafe { arr_obj.as_array() };
-            let n_rows = array.shape()[0];
-            let n_cols = array.shape()[1];
-            let mut policy_vec: Vec<agent::Action> = Vec::with_capacity(n_rows * n_cols);
-            for row in 0..n_rows {
-                for col in 0..n_cols {
-                    let item = array.get([col, row]).unwrap();
-                    let action: agent::Action = item.extract(py)?;
-                    policy_vec.push(action);
-                }
-            }
+        let policy = policy::extract_policy(py, &policy_any, &initial_state.board, layout)?;
+        let comparison_policy = comparison_policy
+            .map(|policy| policy::extract_policy(py, &policy, &initial_state.board, layout))
+            .transpose()?;
 
-            Ok(policy_vec)
-        } else {
-            Err(PyTypeError::new_err(
-                "Policy must be a numpy.ndarray in non-headless mode",
-            ))
-        }?;
+        match config.renderer {
+            Renderer::Window => {
+                viewer::claim_window()?;
+                let mut app = generate_app(config, initial_state, policy, comparison_policy, None);
+                app.run();
+            }
+            Renderer::Terminal if comparison_policy.is_some() => {
+                return Err(PyValueError::new_err(
+                    "comparison_policy is only supported by the Window renderer",
+                ));
+            }
+            Renderer::Terminal => {
+                tui::run(initial_state, &policy, &config).map_err(|e| {
+                    PyRuntimeError::new_err(format!("Terminal renderer failed: {}", e))
+                })?;
+            }
+        }
 
-        let mut app = generate_app(config, policy);
-        app.run();
         Ok(None)
     } else {
         Ok(Some((initial_state, generation_seed, episode_seed)))
     }
 }
 
+/// Opens the game for `config` like `run`, but returns at once with a `Viewer` that controls
+/// the window while Python keeps running. The app runs on a thread of its own, so the window
+/// stays responsive between calls. Without a `policy` it starts paused on a uniformly random
+/// one until `Viewer.set_policy` is called. Only one window, opened either here or by `run`,
+/// can ever be opened per Python process: the windowing library cannot create another one
+/// after the first closes. Not supported on macOS, which only opens windows on the main thread.
+#[gen_stub_pyfunction]
+#[pyfunction(name = "open_viewer")]
+#[pyo3(signature=(config, policy=None, layout=PolicyLayout::WidthHeight, board=None))]
+fn open_viewer(
+    py: Python<'_>,
+    mut config: GGConfig,
+    policy: Option<Py<PyAny>>,
+    layout: PolicyLayout,
+    board: Option<Board>,
+) -> PyResult<viewer::Viewer> {
+    config.check()?;
+    if config.headless || config.renderer != Renderer::Window {
+        return Err(PyValueError::new_err(
+            "open_viewer needs a windowed config: headless off and the Window renderer",
+        ));
+    }
+
+    let (initial_state, _, _) = initial_state(&mut config, board)?;
+    let policy = policy
+        .map(|policy| policy::extract_policy(py, &policy, &initial_state.board, layout))
+        .transpose()?;
+
+    viewer::Viewer::open(config, initial_state, policy, layout)
+}
+
+/// Plays one episode of `policy` without opening a window and writes every frame to `path`,
+/// either as an animated GIF (when `path` ends in `.gif`) or as a numbered PNG sequence in
+/// the directory `path`. Returns the number of frames written.
+#[gen_stub_pyfunction]
+#[pyfunction(name = "render_episode")]
+#[pyo3(signature=(config, policy, path, layout=PolicyLayout::WidthHeight, cell_pixels=16, max_steps=200))]
+fn render_episode(
+    py: Python<'_>,
+    mut config: GGConfig,
+    policy: Py<PyAny>,
+    path: &str,
+    layout: PolicyLayout,
+    cell_pixels: u32,
+    max_steps: usize,
+) -> PyResult<usize> {
+    if cell_pixels == 0 {
+        return Err(PyValueError::new_err("cell_pixels must be positive"));
+    }
+
+    config.check()?;
+    let (state, generation_seed, episode_seed) = initial_state(&mut config, None)?;
+    let policy = policy::extract_policy(py, &policy, &state.board, layout)?;
+
+    let banner = [
+        format!("Generation Seed: {}", generation_seed),
+        format!("Episode Seed: {}", episode_seed),
+    ];
+
+    let (states, _) = policy::rollout(state, &policy, max_steps)?;
+    let frames = states
+        .iter()
+        .map(|state| export::render_frame(state, cell_pixels, &banner))
+        .collect::<Vec<_>>();
+
+    let num_frames = frames.len();
+    export::write_frames(frames, std::path::Path::new(path), config.render_delay_secs)
+        .map_err(|e| PyRuntimeError::new_err(format!("Failed to write episode: {}", e)))?;
+
+    Ok(num_frames)
+}
+
+/// Plays one episode of `policy` without rendering it. Returns every visited state, starting
+/// with the initial state, and the actions taken between them.
+#[gen_stub_pyfunction]
+#[pyfunction(name = "rollout")]
+#[pyo3(signature=(config, policy, layout=PolicyLayout::WidthHeight, max_steps=200))]
+fn rollout(
+    py: Python<'_>,
+    mut config: GGConfig,
+    policy: Py<PyAny>,
+    layout: PolicyLayout,
+    max_steps: usize,
+) -> PyResult<(Vec<GameState>, Vec<agent::Action>)> {
+    config.check()?;
+    let (state, _, _) = initial_state(&mut config, None)?;
+    let policy = policy::extract_policy(py, &policy, &state.board, layout)?;
+
+    Ok(policy::rollout(state, &policy, max_steps)?)
+}
+
+/// Plans one move from `state` with Monte Carlo tree search (UCT) and returns the most
+/// visited action with the visit and value statistics of every action. Simulations leave the
+/// tree with uniformly random actions unless `rollout_policy` is given. Returns are scaled to
+/// [-1, 1] by `goblets.max_reward`, so `exploration` is relative to that scale.
+#[gen_stub_pyfunction]
+#[pyfunction(name = "mcts")]
+#[pyo3(signature=(state, iterations=1000, exploration=std::f64::consts::SQRT_2, max_depth=100, discount=0.99, rollout_policy=None, layout=PolicyLayout::WidthHeight))]
+#[allow(clippy::too_many_arguments)]
+fn mcts(
+    py: Python<'_>,
+    state: GameState,
+    iterations: usize,
+    exploration: f64,
+    max_depth: usize,
+    discount: f64,
+    rollout_policy: Option<Py<PyAny>>,
+    layout: PolicyLayout,
+) -> PyResult<(agent::Action, Vec<planner::ActionStats>)> {
+    if iterations == 0 {
+        return Err(PyValueError::new_err("iterations must be positive"));
+    }
+
+    let rollout_policy = match rollout_policy {
+        Some(policy) => planner::RolloutPolicy::Policy(policy::extract_policy(
+            py,
+            &policy,
+            &state.board,
+            layout,
+        )?),
+        None => planner::RolloutPolicy::Random,
+    };
+
+    let mcts = planner::Mcts {
+        iterations,
+        exploration,
+        max_depth,
+        discount,
+    };
+
+    Ok(mcts.search(&state, &rollout_policy)?)
+}
+
+/// Counts how often the agent stands on each cell over `states`, e.g. the states returned by
+/// `rollout`, as an array indexed like a policy with the same `layout`. All states must be on
+/// boards of the same size.
+#[gen_stub_pyfunction]
+#[pyfunction(name = "visitation_counts")]
+#[pyo3(signature=(states, layout=PolicyLayout::WidthHeight))]
+fn visitation_counts<'py>(
+    py: Python<'py>,
+    states: Vec<GameState>,
+    layout: PolicyLayout,
+) -> PyResult<Bound<'py, PyArray2<u32>>> {
+    let Some(first) = states.first() else {
+        return Err(PyValueError::new_err("states must not be empty"));
+    };
+    let (width, height) = (first.board.width, first.board.height);
+    if let Some(state) = states
+        .iter()
+        .find(|state| (state.board.width, state.board.height) != (width, height))
+    {
+        return Err(PyValueError::new_err(format!(
+            "all states must share a {}x{} board, got a {}x{} one",
+            width, height, state.board.width, state.board.height
+        )));
+    }
+
+    let visitation = game_state::Visitation::from_states(&states);
+    let counts = match layout {
+        PolicyLayout::WidthHeight => (0..width)
+            .flat_map(|x| (0..height).map(move |y| (x, y)))
+            .map(|cell| visitation.count(cell))
+            .collect(),
+        PolicyLayout::HeightWidth => visitation.counts,
+    };
+    let shape = match layout {
+        PolicyLayout::WidthHeight => [width, height],
+        PolicyLayout::HeightWidth => [height, width],
+    };
+
+    PyArray1::from_vec(py, counts).reshape(shape)
+}
+
+/// The Q-table and per-episode returns produced by `train_tabular`.
+type TrainingResult<'py> = (Bound<'py, PyArray2<f64>>, Bound<'py, PyArray1<f64>>);
+
+/// Trains a tabular agent with `algorithm` on episodes that all start from `state`, acting
+/// epsilon-greedily. `epsilon` and `learning_rate` are looked up per episode, and every random
+/// draw comes from `seed`, so the same arguments always give the same result. Returns the
+/// Q-table, with one row per `GameState.state_index` and columns in action tag order, and the
+/// undiscounted return of every episode. A ghost capture is scored as `-goblets.max_reward`.
+#[gen_stub_pyfunction]
+#[pyfunction(name = "train_tabular")]
+#[pyo3(signature=(state, algorithm, episodes=1000, discount=0.99, epsilon=tabular::Schedule::Constant { value: 0.1 }, learning_rate=tabular::Schedule::Constant { value: 0.1 }, max_steps=200, seed=0))]
+#[allow(clippy::too_many_arguments)]
+fn train_tabular<'py>(
+    py: Python<'py>,
+    state: GameState,
+    algorithm: tabular::TdAlgorithm,
+    episodes: usize,
+    discount: f64,
+    epsilon: tabular::Schedule,
+    learning_rate: tabular::Schedule,
+    max_steps: usize,
+    seed: u64,
+) -> PyResult<TrainingResult<'py>> {
+    let trainer = tabular::TdTrainer {
+        algorithm,
+        episodes,
+        discount,
+        epsilon,
+        learning_rate,
+        max_steps,
+        seed,
+    };
+
+    let result = py.detach(|| trainer.train(&state))?;
+
+    let num_states = result.q_table.len();
+    let q_table = PyArray1::from_vec(py, result.q_table.concat())
+        .reshape([num_states, agent::Action::ALL.len()])?;
+    let returns = PyArray1::from_vec(py, result.returns);
+
+    Ok((q_table, returns))
+}
+
+/// Loads a scenario from the built-in registry. `name` may pin a version as `name@version`;
+/// a bare name loads its latest version. Versions never change once released, so a pinned
+/// scenario always has the same board.
+#[gen_stub_pyfunction]
+#[pyfunction(name = "load_scenario")]
+fn load_scenario(name: &str) -> GGResult<scenario::Scenario> {
+    scenario::Scenario::load(name)
+}
+
+/// The `name@version` of every scenario in the built-in registry.
+#[gen_stub_pyfunction]
+#[pyfunction(name = "scenario_names")]
+fn scenario_names() -> Vec<String> {
+    scenario::names()
+}
+
 fn force_focus(
     mut done: Local<bool>,
     winit: Option<NonSend<WinitWindows>>,
@@ -149,19 +433,86 @@ fn force_focus(
 }
 
 #[pymodule]
-fn _core(_py: Python, m: &Bound<'_, PyModule>) -> PyResult<()> {
+fn _core(py: Python, m: &Bound<'_, PyModule>) -> PyResult<()> {
     m.add_function(wrap_pyfunction!(run, m)?)?;
+    m.add_function(wrap_pyfunction!(open_viewer, m)?)?;
     m.add_function(wrap_pyfunction!(parse_config, m)?)?;
+    m.add_function(wrap_pyfunction!(render_episode, m)?)?;
+    m.add_function(wrap_pyfunction!(rollout, m)?)?;
+    m.add_function(wrap_pyfunction!(mcts, m)?)?;
+    m.add_function(wrap_pyfunction!(train_tabular, m)?)?;
+    m.add_function(wrap_pyfunction!(visitation_counts, m)?)?;
+    m.add_function(wrap_pyfunction!(load_scenario, m)?)?;
+    m.add_function(wrap_pyfunction!(scenario_names, m)?)?;
 
     m.add_class::<core::GGConfig>()?;
+    m.add_class::<core::Renderer>()?;
+    m.add_class::<core::ConfigError>()?;
+    m.add_class::<policy::PolicyLayout>()?;
+    m.add_class::<planner::ActionStats>()?;
+    m.add_class::<tabular::TdAlgorithm>()?;
+    m.add_class::<tabular::Schedule>()?;
+    m.add_class::<scenario::Scenario>()?;
+    m.add_class::<viewer::Viewer>()?;
+
     m.add_class::<agent::Action>()?;
     m.add_class::<agent::AgentConfig>()?;
+    m.add_class::<agent::GhostPolicy>()?;
     m.add_class::<camera::CameraConfig>()?;
+    m.add_class::<camera::CameraMode>()?;
     m.add_class::<game_state::GameState>()?;
+    m.add_class::<game_state::Board>()?;
+    m.add_class::<game_state::Goblet>()?;
     m.add_class::<game_state::EntityType>()?;
+    m.add_class::<goblet::GobletConfig>()?;
     m.add_class::<scene::WorldGenerationConfig>()?;
 
+    m.add(
+        "InvalidConfigError",
+        py.get_type::<error::InvalidConfigError>(),
+    )?;
+    m.add(
+        "BoardGenerationError",
+        py.get_type::<error::BoardGenerationError>(),
+    )?;
+    m.add("PolicyShapeError", py.get_type::<error::PolicyShapeError>())?;
+
     Ok(())
 }
 
 define_stub_info_gatherer!(stub_info);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The agent positions of an episode that always tries to move right.
+    fn trajectory(mut state: GameState) -> Vec<(usize, usize)> {
+        let mut positions = vec![state.board.agent_position];
+        for _ in 0..20 {
+            if state.done {
+                break;
+            }
+            state = state.step(agent::Action::Right);
+            positions.push(state.board.agent_position);
+        }
+        positions
+    }
+
+    #[test]
+    fn reported_seeds_reproduce_the_first_episode() {
+        let mut config = GGConfig::default();
+        config.agent.transition = [0.4, 0.2, 0.2, 0.2];
+        let (state, generation_seed, episode_seed) = initial_state(&mut config, None).unwrap();
+        assert_eq!(config.episode_seed.map(u64::from), Some(episode_seed));
+
+        let mut replay = GGConfig::default();
+        replay.agent.transition = config.agent.transition;
+        replay.generation_seed = Some(generation_seed);
+        replay.episode_seed = Some(episode_seed as u32);
+        let (replayed, _, _) = initial_state(&mut replay, None).unwrap();
+
+        assert_eq!(trajectory(state.clone()), trajectory(replayed));
+        assert_eq!(trajectory(state.clone()), trajectory(state.reset().0));
+    }
+}
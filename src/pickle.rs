@@ -0,0 +1,44 @@
+use pyo3::{prelude::*, types::PyTuple};
+use serde::{Serialize, de::DeserializeOwned};
+
+use crate::error::GGResult;
+
+/// Serializes `value` to the JSON read back by `from_json`. Every serde-backed pyclass exposes
+/// the pair as its `to_json` method and `from_json` static method, and `from_json` restores
+/// the value exactly, so the per-class docs only say what the JSON holds.
+pub fn to_json<T: Serialize>(value: &T) -> GGResult<String> {
+    Ok(serde_json::to_string(value)?)
+}
+
+pub fn from_json<T: DeserializeOwned>(json: &str) -> GGResult<T> {
+    Ok(serde_json::from_str(json)?)
+}
+
+/// `__reduce__` for a serde-backed pyclass: the object is rebuilt by passing its JSON to the
+/// `from_json` static method of `class_name`.
+pub fn reduce<T: Serialize>(
+    py: Python<'_>,
+    class_name: &str,
+    value: &T,
+) -> PyResult<(Py<PyAny>, Py<PyAny>)> {
+    let from_json = py
+        .import("gg_core._core")?
+        .getattr(class_name)?
+        .getattr("from_json")?;
+
+    let args = PyTuple::new(py, [to_json(value)?])?;
+    Ok((from_json.into(), args.into()))
+}
+
+/// `__reduce__` for a fieldless pyclass enum: the variant is looked up by name on the class.
+pub fn reduce_variant(
+    py: Python<'_>,
+    class_name: &str,
+    variant: &str,
+) -> PyResult<(Py<PyAny>, Py<PyAny>)> {
+    let cls = py.import("gg_core._core")?.getattr(class_name)?;
+    let getattr = py.import("builtins")?.getattr("getattr")?;
+
+    let args = PyTuple::new(py, [cls, variant.into_pyobject(py)?.into_any()])?;
+    Ok((getattr.into(), args.into()))
+}
@@ -0,0 +1,269 @@
+use pyo3::prelude::*;
+use pyo3_stub_gen::derive::{gen_stub_pyclass, gen_stub_pymethods};
+use rand::seq::IndexedRandom;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    agent::Action,
+    core::Policy,
+    error::{GGError, GGResult},
+    game_state::GameState,
+    pickle,
+};
+
+/// Visit and value statistics of one action at the root of an MCTS search.
+#[gen_stub_pyclass]
+#[pyclass(name = "ActionStats")]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ActionStats {
+    #[pyo3(get)]
+    pub action: Action,
+    #[pyo3(get)]
+    pub visits: u32,
+    /// Mean discounted return of the simulations that started with this action.
+    #[pyo3(get)]
+    pub value: f64,
+}
+
+#[gen_stub_pymethods]
+#[pymethods]
+impl ActionStats {
+    /// Serializes the action with its visit count and mean value to JSON.
+    fn to_json(&self) -> GGResult<String> {
+        pickle::to_json(self)
+    }
+
+    #[staticmethod]
+    fn from_json(json: &str) -> GGResult<Self> {
+        pickle::from_json(json)
+    }
+
+    fn __reduce__(&self, py: Python) -> PyResult<(Py<PyAny>, Py<PyAny>)> {
+        pickle::reduce(py, "ActionStats", self)
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "ActionStats(action={:?}, visits={}, value={})",
+            self.action, self.visits, self.value
+        )
+    }
+}
+
+/// How actions are picked once a simulation leaves the search tree.
+pub enum RolloutPolicy {
+    /// Every action with equal probability.
+    Random,
+    Policy(Policy),
+}
+
+impl RolloutPolicy {
+    fn action(&self, state: &mut GameState) -> GGResult<Action> {
+        match self {
+            RolloutPolicy::Random => Ok(*Action::ALL
+                .choose(&mut state.rng)
+                .expect("Should have at least one action")),
+            RolloutPolicy::Policy(policy) => {
+                let agent_position = state.board.agent_position;
+                policy
+                    .sample(policy.index(&state.board, agent_position), &mut state.rng)
+                    .ok_or_else(|| {
+                        GGError::PolicyShape(format!("no action for cell {:?}", agent_position))
+                    })
+            }
+        }
+    }
+}
+
+/// An open-loop UCT planner. The tree is keyed by action sequences rather than states, and
+/// every simulation replays them through `GameState::transition` on its own fork of the
+/// root's random stream, so slips and ghost moves are sampled instead of enumerated.
+///
+/// Returns are the terminal reward divided by `goblets.max_reward`, so they lie in [-1, 1]
+/// with a ghost capture scoring -1, and are discounted once per step.
+pub struct Mcts {
+    pub iterations: usize,
+    pub exploration: f64,
+    pub max_depth: usize,
+    pub discount: f64,
+}
+
+#[derive(Debug, Default)]
+struct Node {
+    children: [Option<usize>; 4],
+    visits: u32,
+    value_sum: f64,
+}
+
+impl Node {
+    fn mean_value(&self) -> f64 {
+        if self.visits == 0 {
+            0.0
+        } else {
+            self.value_sum / self.visits as f64
+        }
+    }
+}
+
+impl Mcts {
+    /// Searches from `root` and returns the most visited action with the statistics of every
+    /// root action, in `Action::ALL` order. The search is deterministic given `root`'s random
+    /// stream, which it leaves untouched.
+    pub fn search(
+        &self,
+        root: &GameState,
+        rollout_policy: &RolloutPolicy,
+    ) -> GGResult<(Action, Vec<ActionStats>)> {
+        if root.done {
+            return Err(GGError::InvalidState(
+                "cannot plan from a terminal state".to_string(),
+            ));
+        }
+
+        let mut nodes = vec![Node::default()];
+
+        for iteration in 0..self.iterations {
+            let mut state = root.fork(iteration as u64);
+            let mut path = vec![0];
+
+            // Selection and expansion: follow UCT until a new action is tried.
+            while !state.done && path.len() - 1 < self.max_depth {
+                let node = *path.last().unwrap();
+                let untried = Action::ALL
+                    .iter()
+                    .position(|&action| nodes[node].children[u8::from(action) as usize].is_none());
+
+                let action = match untried {
+                    Some(i) => Action::ALL[i],
+                    None => self.select(&nodes, node),
+                };
+                let slot = u8::from(action) as usize;
+
+                state = state.step(action);
+                let child = match nodes[node].children[slot] {
+                    Some(child) => child,
+                    None => {
+                        nodes.push(Node::default());
+                        nodes[node].children[slot] = Some(nodes.len() - 1);
+                        path.push(nodes.len() - 1);
+                        break;
+                    }
+                };
+                path.push(child);
+            }
+
+            // Rollout: play the rollout policy until the episode ends or the depth runs out.
+            let mut depth = path.len() - 1;
+            while !state.done && depth < self.max_depth {
+                let action = rollout_policy.action(&mut state)?;
+                state = state.step(action);
+                depth += 1;
+            }
+
+            // Backpropagation: a node at depth `d` sees the return discounted by the steps
+            // taken after it.
+            let terminal_value = Self::terminal_value(&state);
+            for (node_depth, &node) in path.iter().enumerate() {
+                let steps_after = (depth - node_depth) as i32;
+                nodes[node].visits += 1;
+                nodes[node].value_sum += terminal_value * self.discount.powi(steps_after);
+            }
+        }
+
+        let stats = Action::ALL
+            .iter()
+            .map(|&action| {
+                let child = nodes[0].children[u8::from(action) as usize].map(|i| &nodes[i]);
+                ActionStats {
+                    action,
+                    visits: child.map_or(0, |node| node.visits),
+                    value: child.map_or(0.0, Node::mean_value),
+                }
+            })
+            .collect::<Vec<_>>();
+
+        let best = stats
+            .iter()
+            .max_by(|a, b| a.visits.cmp(&b.visits).then(a.value.total_cmp(&b.value)))
+            .map(|stats| stats.action)
+            .expect("Should have at least one action");
+
+        Ok((best, stats))
+    }
+
+    fn select(&self, nodes: &[Node], node: usize) -> Action {
+        let log_visits = (nodes[node].visits.max(1) as f64).ln();
+
+        *Action::ALL
+            .iter()
+            .max_by(|&&a, &&b| {
+                let score = |action: Action| {
+                    let child = &nodes[nodes[node].children[u8::from(action) as usize]
+                        .expect("Every action has been tried")];
+                    child.mean_value()
+                        + self.exploration * (log_visits / child.visits as f64).sqrt()
+                };
+                score(a).total_cmp(&score(b))
+            })
+            .expect("Should have at least one action")
+    }
+
+    fn terminal_value(state: &GameState) -> f64 {
+        if !state.done {
+            return 0.0;
+        }
+        if state.reward == i32::MIN {
+            return -1.0;
+        }
+
+        let max_reward = state.config.goblets.max_reward.max(1) as f64;
+        (state.reward as f64 / max_reward).clamp(-1.0, 1.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use super::*;
+    use crate::{
+        core::GGConfig,
+        game_state::{Board, Goblet},
+    };
+
+    #[test]
+    fn uct_steps_onto_an_adjacent_goblet() {
+        // #####
+        // #.A$#
+        // #####
+        let board = Board {
+            agent_position: (2, 1),
+            ghost_position: None,
+            goblets: vec![Goblet {
+                position: (3, 1),
+                reward: 10,
+            }],
+            wall_positions: (0..5)
+                .flat_map(|x| [(x, 0), (x, 2)])
+                .chain([(0, 1), (4, 1)])
+                .collect::<HashSet<_>>(),
+            width: 5,
+            height: 3,
+        };
+        let mut config = GGConfig::default();
+        config.goblets.max_reward = 10;
+        let root = GameState::from(board).with_config(&config).with_seed(0);
+
+        let mcts = Mcts {
+            iterations: 200,
+            exploration: std::f64::consts::SQRT_2,
+            max_depth: 10,
+            discount: 0.95,
+        };
+        let (action, stats) = mcts.search(&root, &RolloutPolicy::Random).unwrap();
+
+        assert_eq!(action, Action::Right);
+        let right = &stats[u8::from(Action::Right) as usize];
+        assert_eq!(right.value, 1.0);
+    }
+}
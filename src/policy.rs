@@ -0,0 +1,539 @@
+use numpy::{
+    PyArrayDescrMethods, PyArrayDyn, PyArrayMethods, PyUntypedArray, PyUntypedArrayMethods,
+};
+use pyo3::{exceptions::PyTypeError, prelude::*};
+use pyo3_stub_gen::derive::{gen_stub_pyclass_enum, gen_stub_pymethods};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    agent::Action,
+    core::Policy,
+    error::{GGError, GGResult},
+    game_state::{Board, EntityType, GameState},
+    pickle,
+};
+
+/// How the axes of a policy array map onto the board.
+#[gen_stub_pyclass_enum]
+#[pyclass]
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub enum PolicyLayout {
+    /// `policy[x, y]`, matching the `(x, y)` tuples used by `Board`.
+    #[default]
+    WidthHeight,
+    /// `policy[y, x]`, i.e. one row of the array per row of the board.
+    HeightWidth,
+}
+
+#[gen_stub_pymethods]
+#[pymethods]
+impl PolicyLayout {
+    fn __reduce__(&self, py: Python) -> PyResult<(Py<PyAny>, Py<PyAny>)> {
+        pickle::reduce_variant(py, "PolicyLayout", &format!("{:?}", self))
+    }
+}
+
+/// A policy entry: the agent cell, plus the ghost cell for ghost-aware policies.
+type Entry = ((usize, usize), Option<(usize, usize)>);
+
+/// Largest number of uncovered cells listed in a `PolicyShapeError` message.
+const MAX_REPORTED_CELLS: usize = 5;
+
+/// How far the probabilities of a cell may sum from 1 before the policy is rejected.
+const PROBABILITY_TOLERANCE: f32 = 1e-3;
+
+/// Reads a numpy policy into a `Policy` over the cells of `board`.
+///
+/// A 2D array holds one action per agent cell, either as `Action`s (object dtype) or as
+/// integer `Action.from_int` tags. A 4D array holds one action per pair of agent and ghost
+/// cells, with the agent axes first, so the action can depend on where the ghost is. Either
+/// may gain a trailing axis of 4 holding action probabilities in tag order. Walls and goblets
+/// may be left empty with `None`, a negative tag or all-zero probabilities; every other cell
+/// must have an action.
+pub fn extract_policy(
+    py: Python<'_>,
+    policy: &Py<PyAny>,
+    board: &Board,
+    layout: PolicyLayout,
+) -> PyResult<Policy> {
+    let policy = policy.bind(py);
+    let array = policy
+        .cast::<PyUntypedArray>()
+        .map_err(|_| PyTypeError::new_err("Policy must be a numpy.ndarray"))?;
+
+    let shape = array.shape();
+    let ghost_aware = shape.len() >= 4;
+    let probabilistic = shape.len() == 3 || shape.len() == 5;
+
+    if ghost_aware && board.ghost_position.is_none() {
+        return Err(GGError::PolicyShape(
+            "a ghost-aware policy was given for a board without a ghost".to_string(),
+        )
+        .into());
+    }
+
+    let axes = |(x, y): (usize, usize)| match layout {
+        PolicyLayout::WidthHeight => [x, y],
+        PolicyLayout::HeightWidth => [y, x],
+    };
+
+    let grid_shape = axes((board.width, board.height));
+    let mut expected_shape = grid_shape.to_vec();
+    if ghost_aware {
+        expected_shape.extend(grid_shape);
+    }
+    if probabilistic {
+        expected_shape.push(Action::ALL.len());
+    }
+
+    if shape != expected_shape.as_slice() {
+        return Err(GGError::PolicyShape(format!(
+            "expected an array of shape {:?} for a {}x{} board with layout {:?}, got {:?}",
+            expected_shape, board.width, board.height, layout, shape
+        ))
+        .into());
+    }
+
+    let entries = entries(board, ghost_aware);
+    let index = |(agent, ghost): ((usize, usize), Option<(usize, usize)>)| {
+        let mut index = axes(agent).to_vec();
+        index.extend(ghost.map(axes).into_iter().flatten());
+        index
+    };
+
+    let distributions = match (probabilistic, array.dtype().kind()) {
+        (false, b'O') => {
+            let array = policy.cast::<PyArrayDyn<Py<PyAny>>>()?.readonly();
+            let array = array.as_array();
+            entries
+                .iter()
+                .map(|&entry| {
+                    let item = &array[index(entry).as_slice()];
+                    if item.is_none(py) {
+                        Ok(None)
+                    } else if let Ok(action) = item.extract::<Action>(py) {
+                        Ok(Some(Policy::one_hot(action)))
+                    } else {
+                        let tag = item.extract::<i64>(py).map_err(|_| {
+                            PyTypeError::new_err(format!(
+                                "Policy entry for {} must be an Action, an integer tag or None",
+                                describe_entry(entry)
+                            ))
+                        })?;
+                        Ok(action_from_tag(tag, entry)?.map(Policy::one_hot))
+                    }
+                })
+                .collect::<PyResult<Vec<_>>>()?
+        }
+        (false, b'i' | b'u') => {
+            let array = policy
+                .call_method1("astype", ("int64",))?
+                .cast_into::<PyArrayDyn<i64>>()?
+                .readonly();
+            let array = array.as_array();
+            entries
+                .iter()
+                .map(|&entry| {
+                    Ok(
+                        action_from_tag(array[index(entry).as_slice()], entry)?
+                            .map(Policy::one_hot),
+                    )
+                })
+                .collect::<GGResult<Vec<_>>>()?
+        }
+        (true, b'f' | b'i' | b'u') => {
+            let array = policy
+                .call_method1("astype", ("float32",))?
+                .cast_into::<PyArrayDyn<f32>>()?
+                .readonly();
+            let array = array.as_array();
+            entries
+                .iter()
+                .map(|&entry| {
+                    let mut position = index(entry);
+                    position.push(0);
+                    let probabilities = Action::ALL.map(|action| {
+                        *position.last_mut().unwrap() = u8::from(action) as usize;
+                        array[position.as_slice()]
+                    });
+                    probabilities_for(probabilities, entry)
+                })
+                .collect::<GGResult<Vec<_>>>()?
+        }
+        (_, kind) => {
+            return Err(PyTypeError::new_err(format!(
+                "Policy array must hold Actions, integer tags or action probabilities, got dtype kind '{}'",
+                kind as char
+            )));
+        }
+    };
+
+    let policy = Policy {
+        probabilities: distributions,
+        ghost_aware,
+    };
+    check_coverage(&policy, board)?;
+    Ok(policy)
+}
+
+/// Checks that `policy` has an action wherever the agent can stand on `board`, so that it
+/// never runs out of actions there. Walls can never be occupied and goblets and ghost
+/// captures end the episode, so their entries may be empty. Boards change after a policy is
+/// read, e.g. in the editor or through `Viewer.show`, so every new board is checked again.
+pub fn check_coverage(policy: &Policy, board: &Board) -> GGResult<()> {
+    if policy.ghost_aware && board.ghost_position.is_none() {
+        return Err(GGError::PolicyShape(
+            "a ghost-aware policy was given for a board without a ghost".to_string(),
+        ));
+    }
+
+    let entries = entries(board, policy.ghost_aware);
+    if policy.probabilities.len() != entries.len() {
+        return Err(GGError::PolicyShape(format!(
+            "the policy has {} entries but a {}x{} board needs {}",
+            policy.probabilities.len(),
+            board.width,
+            board.height,
+            entries.len()
+        )));
+    }
+
+    let uncovered = entries
+        .iter()
+        .zip(&policy.probabilities)
+        .filter(|(_, probabilities)| probabilities.is_none())
+        .map(|(entry, _)| *entry)
+        .filter(|(agent, ghost)| {
+            !matches!(board.get(agent), EntityType::Wall() | EntityType::Goblet(_))
+                && *ghost != Some(*agent)
+        })
+        .collect::<Vec<_>>();
+
+    if uncovered.is_empty() {
+        return Ok(());
+    }
+
+    Err(GGError::PolicyShape(format!(
+        "{} free cells have no action, e.g. {}",
+        uncovered.len(),
+        uncovered
+            .iter()
+            .take(MAX_REPORTED_CELLS)
+            .map(|entry| describe_entry(*entry))
+            .collect::<Vec<_>>()
+            .join(", ")
+    )))
+}
+
+/// Every policy entry of `board`, ordered like `Policy::index`: agent cell major, ghost cell
+/// minor.
+fn entries(board: &Board, ghost_aware: bool) -> Vec<Entry> {
+    let cells = (0..board.height)
+        .flat_map(|y| (0..board.width).map(move |x| (x, y)))
+        .collect::<Vec<_>>();
+    if ghost_aware {
+        cells
+            .iter()
+            .flat_map(|&agent| cells.iter().map(move |&ghost| (agent, Some(ghost))))
+            .collect()
+    } else {
+        cells.iter().map(|&agent| (agent, None)).collect()
+    }
+}
+
+/// Plays `policy` from `state` until the episode ends or `max_steps` actions have been taken,
+/// sampling actions with the episode's random stream. Returns every visited state, starting
+/// with `state`, and the actions taken between them.
+pub fn rollout(
+    mut state: GameState,
+    policy: &Policy,
+    max_steps: usize,
+) -> GGResult<(Vec<GameState>, Vec<Action>)> {
+    let mut states = vec![state.clone()];
+    let mut actions = Vec::new();
+
+    while !state.done && actions.len() < max_steps {
+        let agent_position = state.board.agent_position;
+        let action = policy
+            .sample(policy.index(&state.board, agent_position), &mut state.rng)
+            .ok_or_else(|| {
+                GGError::PolicyShape(format!("no action for cell {:?}", agent_position))
+            })?;
+
+        state = state.step(action);
+        states.push(state.clone());
+        actions.push(action);
+    }
+
+    Ok((states, actions))
+}
+
+fn describe_entry((agent, ghost): Entry) -> String {
+    match ghost {
+        Some(ghost) => format!("agent {:?} with ghost {:?}", agent, ghost),
+        None => format!("cell {:?}", agent),
+    }
+}
+
+fn action_from_tag(tag: i64, entry: Entry) -> GGResult<Option<Action>> {
+    if tag < 0 {
+        return Ok(None);
+    }
+
+    u8::try_from(tag)
+        .ok()
+        .and_then(|tag| Action::try_from(tag).ok())
+        .map(Some)
+        .ok_or_else(|| {
+            GGError::PolicyShape(format!(
+                "invalid action tag {} for {}; expected 0-3 or a negative value for no action",
+                tag,
+                describe_entry(entry)
+            ))
+        })
+}
+
+fn probabilities_for(probabilities: [f32; 4], entry: Entry) -> GGResult<Option<[f32; 4]>> {
+    if probabilities.iter().any(|p| !(p.is_finite() && *p >= 0.0)) {
+        return Err(GGError::PolicyShape(format!(
+            "action probabilities for {} must be finite and non-negative, got {:?}",
+            describe_entry(entry),
+            probabilities
+        )));
+    }
+
+    let total = probabilities.iter().sum::<f32>();
+    if total == 0.0 {
+        return Ok(None);
+    }
+
+    if (total - 1.0).abs() > PROBABILITY_TOLERANCE {
+        return Err(GGError::PolicyShape(format!(
+            "action probabilities for {} must sum to 1, got {:?} (sum {})",
+            describe_entry(entry),
+            probabilities,
+            total
+        )));
+    }
+
+    Ok(Some(probabilities.map(|p| p / total)))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::HashSet, ffi::CString};
+
+    use pyo3::types::PyDict;
+
+    use super::*;
+    use crate::{error::PolicyShapeError, game_state::Goblet};
+
+    /// Runs `test` with the interpreter started, or skips it when numpy is not installed.
+    fn with_numpy(test: impl FnOnce(Python<'_>)) {
+        Python::initialize();
+        Python::attach(|py| {
+            if py.import("numpy").is_err() {
+                eprintln!("numpy is not installed, skipping");
+                return;
+            }
+            test(py);
+        });
+    }
+
+    /// Runs `code`, which may use `np` and `Action`, and returns the array it binds to `policy`.
+    fn array(py: Python<'_>, code: &str) -> Py<PyAny> {
+        let globals = PyDict::new(py);
+        globals.set_item("np", py.import("numpy").unwrap()).unwrap();
+        globals.set_item("Action", py.get_type::<Action>()).unwrap();
+        py.run(&CString::new(code).unwrap(), Some(&globals), None)
+            .unwrap_or_else(|e| panic!("Failed to run {:?}: {}", code, e));
+        globals.get_item("policy").unwrap().unwrap().unbind()
+    }
+
+    /// A 3x2 board without walls, with a goblet in the bottom right corner.
+    fn board(ghost_position: Option<(usize, usize)>) -> Board {
+        Board {
+            agent_position: (0, 0),
+            ghost_position,
+            goblets: vec![Goblet {
+                position: (2, 1),
+                reward: 1,
+            }],
+            wall_positions: HashSet::new(),
+            width: 3,
+            height: 2,
+        }
+    }
+
+    fn action(tag: usize) -> Action {
+        Action::try_from(tag as u8).unwrap()
+    }
+
+    #[test]
+    fn reads_integer_tags_in_either_layout() {
+        with_numpy(|py| {
+            let board = board(None);
+
+            let policy = array(py, "policy = np.arange(6).reshape(3, 2) % 4");
+            let policy = extract_policy(py, &policy, &board, PolicyLayout::WidthHeight).unwrap();
+            assert!(!policy.ghost_aware);
+            for (x, y) in [(0, 0), (1, 0), (2, 0), (0, 1), (1, 1)] {
+                assert_eq!(
+                    policy.deterministic_action(policy.index(&board, (x, y))),
+                    Some(action((2 * x + y) % 4))
+                );
+            }
+
+            let policy = array(
+                py,
+                "policy = np.arange(6, dtype=np.uint8).reshape(2, 3) % 4",
+            );
+            let policy = extract_policy(py, &policy, &board, PolicyLayout::HeightWidth).unwrap();
+            for (x, y) in [(0, 0), (1, 0), (2, 0), (0, 1), (1, 1)] {
+                assert_eq!(
+                    policy.deterministic_action(policy.index(&board, (x, y))),
+                    Some(action((3 * y + x) % 4))
+                );
+            }
+        });
+    }
+
+    #[test]
+    fn rejects_an_array_shaped_for_the_other_layout() {
+        with_numpy(|py| {
+            let policy = array(py, "policy = np.zeros((3, 2), dtype=int)");
+            let error =
+                extract_policy(py, &policy, &board(None), PolicyLayout::HeightWidth).unwrap_err();
+            assert!(error.is_instance_of::<PolicyShapeError>(py));
+        });
+    }
+
+    #[test]
+    fn reads_actions_tags_and_none_from_object_arrays() {
+        with_numpy(|py| {
+            let board = board(None);
+            let policy = array(
+                py,
+                "policy = np.full((3, 2), Action.Down, dtype=object)\n\
+                 policy[1, 1] = 3\n\
+                 policy[2, 1] = None",
+            );
+            let policy = extract_policy(py, &policy, &board, PolicyLayout::WidthHeight).unwrap();
+
+            assert_eq!(
+                policy.deterministic_action(policy.index(&board, (0, 0))),
+                Some(Action::Down)
+            );
+            assert_eq!(
+                policy.deterministic_action(policy.index(&board, (1, 1))),
+                Some(Action::Right)
+            );
+        });
+    }
+
+    #[test]
+    fn reads_float_and_integer_probabilities() {
+        with_numpy(|py| {
+            let board = board(None);
+
+            let policy = array(py, "policy = np.full((3, 2, 4), 0.25)");
+            let policy = extract_policy(py, &policy, &board, PolicyLayout::WidthHeight).unwrap();
+            assert_eq!(policy.probabilities.len(), 6);
+            assert_eq!(
+                policy.probabilities[policy.index(&board, (1, 0))],
+                Some([0.25; 4])
+            );
+
+            let policy = array(py, "policy = np.eye(4, dtype=int)[np.full((2, 3), 2)]");
+            let policy = extract_policy(py, &policy, &board, PolicyLayout::HeightWidth).unwrap();
+            assert_eq!(
+                policy.deterministic_action(policy.index(&board, (1, 0))),
+                Some(Action::Left)
+            );
+        });
+    }
+
+    #[test]
+    fn reads_ghost_aware_policies() {
+        with_numpy(|py| {
+            let board = board(Some((1, 1)));
+
+            let policy = array(
+                py,
+                "policy = np.ones((3, 2, 3, 2), dtype=int)\n\
+                 policy[0, 0, 1, 1] = 3",
+            );
+            let policy = extract_policy(py, &policy, &board, PolicyLayout::WidthHeight).unwrap();
+            assert!(policy.ghost_aware);
+            assert_eq!(policy.probabilities.len(), 36);
+            assert_eq!(
+                policy.deterministic_action(policy.index(&board, (0, 0))),
+                Some(Action::Right)
+            );
+            assert_eq!(
+                policy.deterministic_action(policy.index(&board, (1, 0))),
+                Some(Action::Down)
+            );
+
+            let policy = array(
+                py,
+                "policy = np.full((2, 3, 2, 3, 4), 0.25, dtype=np.float32)",
+            );
+            let policy = extract_policy(py, &policy, &board, PolicyLayout::HeightWidth).unwrap();
+            assert!(policy.ghost_aware);
+            assert_eq!(policy.probabilities.len(), 36);
+            assert_eq!(
+                policy.probabilities[policy.index(&board, (2, 0))],
+                Some([0.25; 4])
+            );
+        });
+    }
+
+    #[test]
+    fn rejects_a_ghost_aware_policy_without_a_ghost() {
+        with_numpy(|py| {
+            let policy = array(py, "policy = np.zeros((3, 2, 3, 2), dtype=int)");
+            let error =
+                extract_policy(py, &policy, &board(None), PolicyLayout::WidthHeight).unwrap_err();
+            assert!(error.is_instance_of::<PolicyShapeError>(py));
+        });
+    }
+
+    #[test]
+    fn reports_free_cells_without_an_action() {
+        with_numpy(|py| {
+            let board = board(None);
+
+            // The goblet cell may be left empty.
+            let policy = array(
+                py,
+                "policy = np.zeros((3, 2), dtype=int)\npolicy[2, 1] = -1",
+            );
+            assert!(extract_policy(py, &policy, &board, PolicyLayout::WidthHeight).is_ok());
+
+            let policy = array(
+                py,
+                "policy = np.zeros((3, 2), dtype=int)\npolicy[1, 0] = -1",
+            );
+            let error = extract_policy(py, &policy, &board, PolicyLayout::WidthHeight).unwrap_err();
+            assert!(error.is_instance_of::<PolicyShapeError>(py));
+            assert!(error.to_string().contains("cell (1, 0)"));
+        });
+    }
+
+    #[test]
+    fn coverage_is_checked_again_when_the_board_opens_a_cell() {
+        let mut board = board(None);
+        let policy = Policy {
+            probabilities: (0..6)
+                .map(|i| (i != 5).then_some(Policy::one_hot(Action::Up)))
+                .collect(),
+            ghost_aware: false,
+        };
+        assert!(check_coverage(&policy, &board).is_ok());
+
+        board.goblets.clear();
+        let error = check_coverage(&policy, &board).unwrap_err();
+        assert!(error.to_string().contains("cell (2, 1)"));
+    }
+}
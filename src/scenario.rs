@@ -0,0 +1,295 @@
+use std::collections::HashSet;
+
+use pyo3::prelude::*;
+use pyo3_stub_gen::derive::{gen_stub_pyclass, gen_stub_pymethods};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    agent::GhostPolicy,
+    core::GGConfig,
+    error::{GGError, GGResult},
+    game_state::{Board, Goblet},
+    pickle,
+};
+
+/// A fixed board and the config it is played with. Every `(name, version)` pair in
+/// `SCENARIOS` is frozen: changing a layout means adding the next version of its name, so
+/// results cited against an older version stay reproducible.
+struct ScenarioSpec {
+    name: &'static str,
+    version: u32,
+    description: &'static str,
+    /// One string per row of the board, top row (`y = 0`) first: `#` is a wall, `.` a free
+    /// cell, `A` the agent, `G` the ghost and `$` a goblet.
+    layout: &'static [&'static str],
+    /// Goblet rewards in the row-major order their `$` appear in `layout`.
+    rewards: &'static [i32],
+    ghost_policy: Option<GhostPolicy>,
+    transition: [f32; 4],
+}
+
+const DETERMINISTIC: [f32; 4] = [1.0, 0.0, 0.0, 0.0];
+
+const SCENARIOS: &[ScenarioSpec] = &[
+    ScenarioSpec {
+        name: "open_room_1goblet",
+        version: 1,
+        description: "An empty room with the agent in one corner and a goblet in the opposite one.",
+        layout: &[
+            "#######", //
+            "#....$#", //
+            "#.....#", //
+            "#.....#", //
+            "#.....#", //
+            "#A....#", //
+            "#######", //
+        ],
+        rewards: &[10],
+        ghost_policy: None,
+        transition: DETERMINISTIC,
+    },
+    ScenarioSpec {
+        name: "narrow_corridor_ghost",
+        version: 1,
+        description: "A one-cell-wide loop with a chasing ghost between the agent and the short way to the goblet.",
+        layout: &[
+            "#########", //
+            "#A.....G#", //
+            "#.#####.#", //
+            "#......$#", //
+            "#########", //
+        ],
+        rewards: &[10],
+        ghost_policy: Some(GhostPolicy::Chaser),
+        transition: DETERMINISTIC,
+    },
+    ScenarioSpec {
+        name: "cliff_walk",
+        version: 1,
+        description: "The classic cliff: the shortest path to the goal runs along a row of goblets that each cost 10.",
+        layout: &[
+            "##############", //
+            "#............#", //
+            "#............#", //
+            "#............#", //
+            "#A$$$$$$$$$$$#", //
+            "##############", //
+        ],
+        rewards: &[-10, -10, -10, -10, -10, -10, -10, -10, -10, -10, 10],
+        ghost_policy: None,
+        transition: DETERMINISTIC,
+    },
+    ScenarioSpec {
+        name: "bridge_crossing",
+        version: 1,
+        description: "A slippery bridge over lava: a small reward next to the start or a large one across the bridge.",
+        layout: &[
+            "#########", //
+            "#$$$$$$$#", //
+            "#$A....$#", //
+            "#$$$$$$$#", //
+            "#########", //
+        ],
+        rewards: &[
+            -10, -10, -10, -10, -10, -10, -10, //
+            1, 10, //
+            -10, -10, -10, -10, -10, -10, -10, //
+        ],
+        ghost_policy: None,
+        transition: [0.8, 0.1, 0.0, 0.1],
+    },
+];
+
+impl ScenarioSpec {
+    fn id(&self) -> String {
+        format!("{}@{}", self.name, self.version)
+    }
+
+    fn board(&self) -> Board {
+        let mut wall_positions = HashSet::new();
+        let mut agent_position = None;
+        let mut ghost_position = None;
+        let mut goblets = Vec::new();
+        let mut rewards = self.rewards.iter();
+
+        for (y, row) in self.layout.iter().enumerate() {
+            for (x, symbol) in row.chars().enumerate() {
+                match symbol {
+                    '#' => {
+                        wall_positions.insert((x, y));
+                    }
+                    'A' => agent_position = Some((x, y)),
+                    'G' => ghost_position = Some((x, y)),
+                    '$' => goblets.push(Goblet {
+                        position: (x, y),
+                        reward: *rewards
+                            .next()
+                            .expect("Scenario should have a reward per goblet"),
+                    }),
+                    '.' => {}
+                    _ => unreachable!("Unknown symbol {:?} in scenario {}", symbol, self.id()),
+                }
+            }
+        }
+
+        Board {
+            wall_positions,
+            agent_position: agent_position.expect("Scenario should place the agent"),
+            ghost_position,
+            goblets,
+            width: self.layout[0].len(),
+            height: self.layout.len(),
+        }
+    }
+
+    /// The default config with the overrides this scenario needs: a world the size of its
+    /// layout, goblet settings that admit its rewards, and its ghost and transition model.
+    fn config(&self) -> GGConfig {
+        let mut config = GGConfig::default();
+
+        let world = &mut config.world_generation;
+        world.world_width = self.layout[0].len() as f32 * world.cell_size;
+        world.world_height = self.layout.len() as f32 * world.cell_size;
+        world.num_obstacles = 0;
+
+        config.goblets.number = self.rewards.len();
+        config.goblets.max_reward = self
+            .rewards
+            .iter()
+            .map(|reward| reward.unsigned_abs())
+            .max()
+            .unwrap_or(0);
+
+        config.agent.ghost_policy = self.ghost_policy.clone();
+        config.agent.transition = self.transition;
+        config.scenario = Some(self.id());
+
+        config
+    }
+}
+
+/// Looks up `id`, either `name@version` or a bare name for its latest version.
+fn find(id: &str) -> GGResult<&'static ScenarioSpec> {
+    let (name, version) = match id.split_once('@') {
+        Some((name, version)) => {
+            let version = version.parse::<u32>().map_err(|_| {
+                GGError::InvalidArgument(format!(
+                    "scenario version must be a whole number, got {:?}",
+                    version
+                ))
+            })?;
+            (name, Some(version))
+        }
+        None => (id, None),
+    };
+
+    SCENARIOS
+        .iter()
+        .filter(|spec| spec.name == name && version.is_none_or(|v| spec.version == v))
+        .max_by_key(|spec| spec.version)
+        .ok_or_else(|| {
+            GGError::InvalidArgument(format!(
+                "unknown scenario {:?}; known scenarios are {}",
+                id,
+                names().join(", ")
+            ))
+        })
+}
+
+/// Checks that `id` names a registered scenario.
+pub fn check(id: &str) -> GGResult<()> {
+    find(id).map(|_| ())
+}
+
+/// The board of the scenario `id`, checked against the size `config` describes.
+pub fn board(id: &str, config: &GGConfig) -> GGResult<Board> {
+    let board = find(id)?.board();
+    board.check_level(config)?;
+    Ok(board)
+}
+
+/// The `name@version` of every registered scenario, in registry order.
+pub fn names() -> Vec<String> {
+    SCENARIOS.iter().map(ScenarioSpec::id).collect()
+}
+
+/// A named benchmark board, returned by `load_scenario`.
+#[gen_stub_pyclass]
+#[pyclass(name = "Scenario")]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Scenario {
+    #[pyo3(get)]
+    pub name: String,
+    #[pyo3(get)]
+    pub version: u32,
+    #[pyo3(get)]
+    pub description: String,
+    #[pyo3(get)]
+    pub board: Board,
+    /// The config to play the scenario with. Its `scenario` field pins this version, so
+    /// `run`, `rollout` and `render_episode` all use exactly this board.
+    #[pyo3(get)]
+    pub config: GGConfig,
+}
+
+impl Scenario {
+    pub fn load(id: &str) -> GGResult<Self> {
+        let spec = find(id)?;
+        Ok(Self {
+            name: spec.name.to_string(),
+            version: spec.version,
+            description: spec.description.to_string(),
+            board: spec.board(),
+            config: spec.config(),
+        })
+    }
+}
+
+#[gen_stub_pymethods]
+#[pymethods]
+impl Scenario {
+    /// Serializes the scenario with its board and config to JSON.
+    fn to_json(&self) -> GGResult<String> {
+        pickle::to_json(self)
+    }
+
+    #[staticmethod]
+    fn from_json(json: &str) -> GGResult<Self> {
+        pickle::from_json(json)
+    }
+
+    fn __reduce__(&self, py: Python) -> PyResult<(Py<PyAny>, Py<PyAny>)> {
+        pickle::reduce(py, "Scenario", self)
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "Scenario(name={:?}, version={}, size={}x{})",
+            self.name, self.version, self.board.width, self.board.height
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn every_scenario_loads_and_fits_its_config() {
+        for id in names() {
+            let spec = find(&id).unwrap();
+            let goblet_symbols = spec
+                .layout
+                .iter()
+                .flat_map(|row| row.chars())
+                .filter(|symbol| *symbol == '$')
+                .count();
+            assert_eq!(goblet_symbols, spec.rewards.len(), "{}", id);
+
+            let scenario = Scenario::load(&id).unwrap();
+            if let Err(e) = scenario.board.check_level(&scenario.config) {
+                panic!("{}: {}", id, e);
+            }
+        }
+    }
+}
@@ -10,6 +10,10 @@ pub struct Wall;
 #[reflect(Component)]
 pub struct GroundPlane;
 
+/// The banner line showing the seed of the episode being played.
+#[derive(Debug, Clone, Copy, Default, Component)]
+pub struct EpisodeSeedText;
+
 #[derive(Debug, Clone, Bundle, Default)]
 pub struct WallBundle {
     pub wall: Wall,
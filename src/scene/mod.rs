@@ -11,10 +11,16 @@ use serde::{Deserialize, Serialize};
 pub use components::*;
 pub use visual::*;
 
-use crate::core::{GGConfig, StartupSets};
+use crate::core::{GGConfig, StartupSets, UpdateSets};
+use crate::error::GGResult;
+use crate::game_state::GameState;
+use crate::pickle;
 
 pub const WALL_HEIGHT: f32 = 5.0;
 
+/// The newest board generation algorithm `WorldGenerationConfig.generator_version` accepts.
+pub const LATEST_GENERATOR_VERSION: u32 = 2;
+
 #[gen_stub_pyclass]
 #[pyclass(name = "WorldGenerationConfig")]
 #[derive(Debug, Clone, Resource, Reflect, Derivative, Serialize, Deserialize)]
@@ -37,6 +43,13 @@ pub struct WorldGenerationConfig {
     #[pyo3(get, set)]
     #[derivative(Default(value = "5.0"))]
     pub cell_size: f32,
+    /// Which board generation algorithm `generation_seed` is fed to. Every version keeps
+    /// producing the same board for the same seed, so boards cited by seed stay reproducible
+    /// as long as the version is kept; newer algorithms are only used when asked for. Version 1
+    /// can put several goblets on one cell; version 2 gives every goblet a cell of its own.
+    #[pyo3(get, set)]
+    #[derivative(Default(value = "1"))]
+    pub generator_version: u32,
 }
 
 #[gen_stub_pymethods]
@@ -44,13 +57,36 @@ pub struct WorldGenerationConfig {
 impl WorldGenerationConfig {
     /// Returns the size of the maze as (width, height)
     #[getter]
-    fn size(&self) -> (usize, usize) {
+    pub fn size(&self) -> (usize, usize) {
         (
             (self.world_width / self.cell_size).round() as usize,
             (self.world_height / self.cell_size).round() as usize,
         )
     }
 
+    /// Serializes the world size, obstacles and generator version to JSON.
+    fn to_json(&self) -> GGResult<String> {
+        pickle::to_json(self)
+    }
+
+    #[staticmethod]
+    fn from_json(json: &str) -> GGResult<Self> {
+        pickle::from_json(json)
+    }
+
+    fn __reduce__(&self, py: Python) -> PyResult<(Py<PyAny>, Py<PyAny>)> {
+        pickle::reduce(py, "WorldGenerationConfig", self)
+    }
+
+    fn __getstate__(&self) -> GGResult<String> {
+        pickle::to_json(self)
+    }
+
+    fn __setstate__(&mut self, state: &str) -> GGResult<()> {
+        *self = pickle::from_json(state)?;
+        Ok(())
+    }
+
     fn __repr__(&self) -> PyResult<String> {
         Ok(format!("MazeGenerationConfig({})", self.__str__()?))
     }
@@ -78,10 +114,15 @@ impl Plugin for ScenePlugin {
         app.add_systems(
             Startup,
             (
-                (systems::setup_scene, systems::spawn_walls).in_set(StartupSets::Walls),
+                (systems::setup_scene, systems::spawn_wall_entities).in_set(StartupSets::Walls),
                 (systems::spawn_seed_text, systems::setup_key_instructions),
             ),
         );
+        app.add_systems(Update, systems::respawn_walls.after(UpdateSets::EditBoard));
+        app.add_systems(
+            Update,
+            systems::update_episode_seed_text.run_if(resource_changed::<GameState>),
+        );
     }
 }
 
@@ -1,11 +1,9 @@
 use bevy::prelude::*;
-use bevy_prng::WyRand;
-use bevy_rand::global::GlobalRng;
 
 use crate::{
     core::GGConfig,
-    game_state::{Board, GameState},
-    scene::{GroundPlane, WALL_HEIGHT, WallBundle, WallGraphicsAssets},
+    game_state::{BoardEditedMessage, GameState},
+    scene::{EpisodeSeedText, GroundPlane, WALL_HEIGHT, Wall, WallBundle, WallGraphicsAssets},
 };
 
 pub fn setup_key_instructions(mut commands: Commands) {
@@ -25,7 +23,7 @@ pub fn setup_key_instructions(mut commands: Commands) {
         ))
         .with_children(|parent| {
             parent.spawn((
-                Text::new("+/-: Zoom In/Out | Arrow Keys: Pan Camera"),
+                Text::new("+/-/Scroll: Zoom In/Out | Arrow Keys: Pan Camera"),
                 TextFont {
                     font_size: 14.0,
                     ..default()
@@ -33,7 +31,31 @@ pub fn setup_key_instructions(mut commands: Commands) {
                 TextLayout::new_with_justify(Justify::Right),
             ));
             parent.spawn((
-                Text::new("P: Toggle Policy Visualization"),
+                Text::new("F: Fit Board | G: Follow Agent | Drag: Orbit (editor closed)"),
+                TextFont {
+                    font_size: 14.0,
+                    ..default()
+                },
+                TextLayout::new_with_justify(Justify::Right),
+            ));
+            parent.spawn((
+                Text::new("P: Toggle Policy Visualization | H: Cycle Trail/Heatmap"),
+                TextFont {
+                    font_size: 14.0,
+                    ..default()
+                },
+                TextLayout::new_with_justify(Justify::Right),
+            ));
+            parent.spawn((
+                Text::new("Space: Pause/Resume | .: Step Forward | ,: Step Backward"),
+                TextFont {
+                    font_size: 14.0,
+                    ..default()
+                },
+                TextLayout::new_with_justify(Justify::Right),
+            ));
+            parent.spawn((
+                Text::new("]/[: Speed Up/Slow Down | R: Restart Episode | E: Board Editor"),
                 TextFont {
                     font_size: 14.0,
                     ..default()
@@ -109,44 +131,63 @@ pub fn spawn_seed_text(mut commands: Commands, config: Res<GGConfig>) {
                     ..default()
                 },
                 TextLayout::new_with_justify(Justify::Right),
+                EpisodeSeedText,
             ));
         });
 }
 
-pub fn spawn_walls(
-    mut rng: Single<&mut WyRand, With<GlobalRng>>,
+/// Shows the seed of the episode being played, which changes whenever a restart or an
+/// auto-reset draws a new one or an edited board is applied.
+pub fn update_episode_seed_text(
+    game_state: Res<GameState>,
+    mut q_text: Query<&mut Text, With<EpisodeSeedText>>,
+) {
+    for mut text in q_text.iter_mut() {
+        text.0 = format!("Episode Seed: {}", game_state.rng_seed);
+    }
+}
+
+pub fn spawn_wall_entities(
     mut commands: Commands,
     mut meshes: Option<ResMut<Assets<Mesh>>>,
     graphics: Option<Res<WallGraphicsAssets>>,
+    game_state: Res<GameState>,
     config: Res<GGConfig>,
 ) {
-    let game_state = GameState::from(Board::new(&mut rng, &config))
-        .with_config(&config)
-        .with_seed(
-            config
-                .episode_seed
-                .expect("Should have generated an episode seed before spawning walls")
-                as u64,
-        );
+    if config.headless {
+        return;
+    }
 
-    if !config.headless {
-        let cell = config.world_generation.cell_size;
+    let cell = config.world_generation.cell_size;
 
-        for (col, row) in &game_state.board.wall_positions {
-            let p0 = (
-                (*col as f32 + 0.5) * cell - (config.world_generation.world_width * 0.5),
-                (*row as f32 + 0.5) * cell - (config.world_generation.world_height * 0.5),
-            );
+    for (col, row) in &game_state.board.wall_positions {
+        let p0 = (
+            (*col as f32 + 0.5) * cell - (config.world_generation.world_width * 0.5),
+            (*row as f32 + 0.5) * cell - (config.world_generation.world_height * 0.5),
+        );
 
-            let mut entity = commands.spawn(WallBundle::new(p0.into(), p0.into()));
+        let mut entity = commands.spawn(WallBundle::new(p0.into(), p0.into()));
 
-            if let (Some(meshes), Some(graphics)) = (&mut meshes, &graphics) {
-                // Square footprint the size of a cell; height = WALL_HEIGHT
-                let mesh = meshes.add(Cuboid::new(cell, WALL_HEIGHT, cell));
-                entity.insert((Mesh3d(mesh), MeshMaterial3d(graphics.material.clone())));
-            }
+        if let (Some(meshes), Some(graphics)) = (&mut meshes, &graphics) {
+            // Square footprint the size of a cell; height = WALL_HEIGHT
+            let mesh = meshes.add(Cuboid::new(cell, WALL_HEIGHT, cell));
+            entity.insert((Mesh3d(mesh), MeshMaterial3d(graphics.material.clone())));
         }
     }
+}
 
-    commands.insert_resource(game_state);
+/// Replaces the walls of the old board with those of an edited one.
+pub fn respawn_walls(
+    mut messages: MessageReader<BoardEditedMessage>,
+    mut commands: Commands,
+    walls: Query<Entity, With<Wall>>,
+) {
+    if messages.read().count() == 0 {
+        return;
+    }
+
+    for entity in walls.iter() {
+        commands.entity(entity).despawn();
+    }
+    commands.run_system_cached(spawn_wall_entities);
 }
@@ -0,0 +1,304 @@
+use pyo3::prelude::*;
+use pyo3_stub_gen::derive::{
+    gen_stub_pyclass_complex_enum, gen_stub_pyclass_enum, gen_stub_pymethods,
+};
+use rand::{Rng, seq::IndexedRandom};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    agent::Action,
+    error::{GGError, GGResult},
+    game_state::GameState,
+    pickle,
+};
+
+/// The temporal-difference update used by `train_tabular`.
+#[gen_stub_pyclass_enum]
+#[pyclass]
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum TdAlgorithm {
+    /// Off-policy: bootstraps from the greedy action in the next state.
+    QLearning,
+    /// On-policy: bootstraps from the action actually taken in the next state.
+    Sarsa,
+    /// Bootstraps from the expectation of the epsilon-greedy policy in the next state.
+    ExpectedSarsa,
+}
+
+#[gen_stub_pymethods]
+#[pymethods]
+impl TdAlgorithm {
+    fn __reduce__(&self, py: Python) -> PyResult<(Py<PyAny>, Py<PyAny>)> {
+        pickle::reduce_variant(py, "TdAlgorithm", &format!("{:?}", self))
+    }
+}
+
+/// A value that changes from episode to episode, such as epsilon or the learning rate.
+#[gen_stub_pyclass_complex_enum]
+#[pyclass]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum Schedule {
+    Constant {
+        value: f64,
+    },
+    /// Moves linearly from `start` to `end` over `episodes` episodes, then stays at `end`.
+    Linear {
+        start: f64,
+        end: f64,
+        episodes: usize,
+    },
+    /// Multiplies `start` by `decay` every episode, never going below `min`.
+    Exponential {
+        start: f64,
+        decay: f64,
+        min: f64,
+    },
+}
+
+#[gen_stub_pymethods]
+#[pymethods]
+impl Schedule {
+    /// Serializes the variant and its parameters to JSON.
+    fn to_json(&self) -> GGResult<String> {
+        pickle::to_json(self)
+    }
+
+    #[staticmethod]
+    fn from_json(json: &str) -> GGResult<Self> {
+        pickle::from_json(json)
+    }
+
+    fn __reduce__(&self, py: Python) -> PyResult<(Py<PyAny>, Py<PyAny>)> {
+        pickle::reduce(py, "Schedule", self)
+    }
+}
+
+impl Schedule {
+    pub fn value(&self, episode: usize) -> f64 {
+        match *self {
+            Schedule::Constant { value } => value,
+            Schedule::Linear {
+                start,
+                end,
+                episodes,
+            } => {
+                let progress = if episodes == 0 {
+                    1.0
+                } else {
+                    (episode as f64 / episodes as f64).min(1.0)
+                };
+                start + (end - start) * progress
+            }
+            Schedule::Exponential { start, decay, min } => {
+                (start * decay.powi(episode.min(i32::MAX as usize) as i32)).max(min)
+            }
+        }
+    }
+
+    /// Checks that every value the schedule can take lies in `range`, naming it `name` in the
+    /// error.
+    fn check(&self, name: &str, range: std::ops::RangeInclusive<f64>) -> GGResult<()> {
+        let bounds = match *self {
+            Schedule::Constant { value } => vec![value],
+            Schedule::Linear { start, end, .. } => vec![start, end],
+            Schedule::Exponential { start, decay, min } => {
+                if !(0.0..=1.0).contains(&decay) {
+                    return Err(GGError::InvalidArgument(format!(
+                        "{} decay must be between 0 and 1, got {}",
+                        name, decay
+                    )));
+                }
+                let highest = start.max(min);
+                vec![highest, min.max(0.0).min(highest)]
+            }
+        };
+
+        match bounds.iter().find(|value| !range.contains(value)) {
+            Some(value) => Err(GGError::InvalidArgument(format!(
+                "{} must stay between {} and {}, got {}",
+                name,
+                range.start(),
+                range.end(),
+                value
+            ))),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Settings for `TdTrainer::train`.
+pub struct TdTrainer {
+    pub algorithm: TdAlgorithm,
+    pub episodes: usize,
+    pub discount: f64,
+    pub epsilon: Schedule,
+    pub learning_rate: Schedule,
+    pub max_steps: usize,
+    pub seed: u64,
+}
+
+/// The result of training: one row of action values per `GameState::state_index`, columns
+/// in `Action::ALL` order, and the undiscounted return of every episode.
+pub struct TdResult {
+    pub q_table: Vec<[f64; 4]>,
+    pub returns: Vec<f64>,
+}
+
+impl TdTrainer {
+    /// Trains an epsilon-greedy agent on episodes that all start from `start`. Episodes draw
+    /// from one random stream seeded with `seed`, so the result only depends on the settings
+    /// and `start`'s board.
+    pub fn train(&self, start: &GameState) -> GGResult<TdResult> {
+        if !(0.0..=1.0).contains(&self.discount) {
+            return Err(GGError::InvalidArgument(format!(
+                "discount must be between 0 and 1, got {}",
+                self.discount
+            )));
+        }
+        self.epsilon.check("epsilon", 0.0..=1.0)?;
+        self.learning_rate.check("learning_rate", 0.0..=1.0)?;
+
+        let mut q_table = vec![[0.0; 4]; start.num_states()];
+        let mut returns = Vec::with_capacity(self.episodes);
+        let mut rng = start.with_seed(self.seed).rng;
+
+        for episode in 0..self.episodes {
+            let epsilon = self.epsilon.value(episode);
+            let alpha = self.learning_rate.value(episode);
+
+            let mut state = start.clone();
+            state.rng = rng;
+
+            let mut index = state.state_index()?;
+            let mut action = epsilon_greedy(&q_table[index], epsilon, &mut state);
+            let mut episode_return = 0.0;
+
+            for _ in 0..self.max_steps {
+                if state.done {
+                    break;
+                }
+
+                state = state.step(action);
+                let reward = reward(&state);
+                episode_return += reward;
+
+                let next_index = state.state_index()?;
+                let next_action = epsilon_greedy(&q_table[next_index], epsilon, &mut state);
+
+                let bootstrap = if state.done {
+                    0.0
+                } else {
+                    let next_values = &q_table[next_index];
+                    match self.algorithm {
+                        TdAlgorithm::QLearning => next_values[greedy(next_values)],
+                        TdAlgorithm::Sarsa => next_values[u8::from(next_action) as usize],
+                        TdAlgorithm::ExpectedSarsa => {
+                            let best = greedy(next_values);
+                            next_values
+                                .iter()
+                                .enumerate()
+                                .map(|(i, value)| {
+                                    let greedy_mass = if i == best { 1.0 - epsilon } else { 0.0 };
+                                    (greedy_mass + epsilon / 4.0) * value
+                                })
+                                .sum()
+                        }
+                    }
+                };
+
+                let value = &mut q_table[index][u8::from(action) as usize];
+                *value += alpha * (reward + self.discount * bootstrap - *value);
+
+                index = next_index;
+                action = next_action;
+            }
+
+            returns.push(episode_return);
+            rng = state.rng;
+        }
+
+        Ok(TdResult { q_table, returns })
+    }
+}
+
+/// The reward for entering `state`. A ghost capture is scored as losing the largest goblet
+/// reward instead of `i32::MIN`, which would swamp every other value in the table.
+fn reward(state: &GameState) -> f64 {
+    if state.done {
+        state.scored_reward()
+    } else {
+        0.0
+    }
+}
+
+/// Index in `Action::ALL` of the highest value, preferring the earliest action on ties.
+fn greedy(values: &[f64; 4]) -> usize {
+    (0..values.len()).fold(0, |best, i| if values[i] > values[best] { i } else { best })
+}
+
+fn epsilon_greedy(values: &[f64; 4], epsilon: f64, state: &mut GameState) -> Action {
+    if state.rng.random::<f64>() < epsilon {
+        *Action::ALL
+            .choose(&mut state.rng)
+            .expect("Should have at least one action")
+    } else {
+        Action::ALL[greedy(values)]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scenario::Scenario;
+
+    /// Follows the greedy action of `q_table` from `start` for at most `max_steps` steps.
+    fn greedy_rollout(q_table: &[[f64; 4]], start: &GameState, max_steps: usize) -> GameState {
+        let mut state = start.clone();
+        for _ in 0..max_steps {
+            if state.done {
+                break;
+            }
+            let index = state.state_index().unwrap();
+            state = state.step(Action::ALL[greedy(&q_table[index])]);
+        }
+        state
+    }
+
+    #[test]
+    fn td_algorithms_learn_to_cross_the_cliff() {
+        let scenario = Scenario::load("cliff_walk@1").unwrap();
+        let start = GameState::from(scenario.board)
+            .with_config(&scenario.config)
+            .with_seed(0);
+
+        for algorithm in [
+            TdAlgorithm::QLearning,
+            TdAlgorithm::Sarsa,
+            TdAlgorithm::ExpectedSarsa,
+        ] {
+            let trainer = TdTrainer {
+                algorithm,
+                episodes: 3000,
+                discount: 0.95,
+                epsilon: Schedule::Linear {
+                    start: 1.0,
+                    end: 0.05,
+                    episodes: 2000,
+                },
+                learning_rate: Schedule::Constant { value: 0.5 },
+                max_steps: 500,
+                seed: 7,
+            };
+            let result = trainer.train(&start).unwrap();
+
+            let end = greedy_rollout(&result.q_table, &start, 100);
+            assert!(
+                end.done && end.reward == 10,
+                "{:?} ended at {:?} with reward {}",
+                algorithm,
+                end.board.agent_position,
+                end.reward
+            );
+        }
+    }
+}
@@ -0,0 +1,236 @@
+use std::io::{self, Write};
+use std::time::{Duration, Instant};
+
+use crossterm::{
+    cursor,
+    event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers},
+    execute, queue,
+    style::{self, Color, Stylize},
+    terminal,
+};
+
+use crate::{
+    agent::Action,
+    core::{GGConfig, Policy},
+    game_state::{EntityType, GameState},
+};
+
+const GROUND_COLOR: Color = Color::Rgb { r: 0, g: 140, b: 0 };
+const WALL_COLOR: Color = Color::Rgb { r: 0, g: 0, b: 0 };
+const AGENT_COLOR: Color = Color::Rgb { r: 255, g: 0, b: 0 };
+const GHOST_COLOR: Color = Color::Rgb {
+    r: 255,
+    g: 255,
+    b: 255,
+};
+const GOBLET_COLOR: Color = Color::Rgb {
+    r: 255,
+    g: 215,
+    b: 0,
+};
+const FALSE_GOBLET_COLOR: Color = Color::Rgb {
+    r: 255,
+    g: 69,
+    b: 0,
+};
+
+/// Characters per board cell: three, or as many as the widest goblet reward of the episode
+/// needs, so large rewards keep the grid aligned.
+fn cell_width(state: &GameState) -> usize {
+    state
+        .initial_board
+        .goblets
+        .iter()
+        .map(|goblet| format!("{:+}", goblet.reward).len())
+        .max()
+        .unwrap_or(0)
+        .max(3)
+}
+
+/// Puts the terminal into raw mode on an alternate screen and restores it when dropped,
+/// so an error while rendering never leaves the user's shell unusable.
+struct TerminalGuard;
+
+impl TerminalGuard {
+    fn new() -> io::Result<Self> {
+        terminal::enable_raw_mode()?;
+        execute!(io::stdout(), terminal::EnterAlternateScreen, cursor::Hide)?;
+        Ok(Self)
+    }
+}
+
+impl Drop for TerminalGuard {
+    fn drop(&mut self) {
+        let _ = execute!(io::stdout(), cursor::Show, terminal::LeaveAlternateScreen);
+        let _ = terminal::disable_raw_mode();
+    }
+}
+
+#[derive(Debug, Default)]
+struct ViewState {
+    paused: bool,
+    show_policy: bool,
+    steps: usize,
+}
+
+/// Plays an episode in the terminal, stepping `policy` every `render_delay_secs`.
+/// Arrow keys move the agent manually (pausing the policy), and the episode can be
+/// paused, reset and quit from the keyboard.
+pub fn run(mut state: GameState, policy: &Policy, config: &GGConfig) -> io::Result<()> {
+    let _guard = TerminalGuard::new()?;
+    let mut stdout = io::stdout();
+
+    let delay = Duration::from_secs_f32(config.render_delay_secs.max(0.0));
+    let mut view = ViewState::default();
+    let mut next_tick = Instant::now() + delay;
+
+    loop {
+        draw(&mut stdout, &state, policy, config, &view)?;
+
+        let timeout = next_tick.saturating_duration_since(Instant::now());
+        if event::poll(timeout)? {
+            let Event::Key(KeyEvent {
+                code,
+                modifiers,
+                kind: KeyEventKind::Press,
+                ..
+            }) = event::read()?
+            else {
+                continue;
+            };
+
+            let manual_action = match code {
+                KeyCode::Char('q') | KeyCode::Esc => return Ok(()),
+                KeyCode::Char('c') if modifiers.contains(KeyModifiers::CONTROL) => return Ok(()),
+                KeyCode::Char(' ') => {
+                    view.paused = !view.paused;
+                    None
+                }
+                KeyCode::Char('p') => {
+                    view.show_policy = !view.show_policy;
+                    None
+                }
+                KeyCode::Char('r') => {
+                    state = state.reset().0;
+                    view.steps = 0;
+                    None
+                }
+                KeyCode::Up => Some(Action::Up),
+                KeyCode::Down => Some(Action::Down),
+                KeyCode::Left => Some(Action::Left),
+                KeyCode::Right => Some(Action::Right),
+                _ => None,
+            };
+
+            if let Some(action) = manual_action
+                && !state.done
+            {
+                view.paused = true;
+                state = state.step(action);
+                view.steps += 1;
+            }
+
+            continue;
+        }
+
+        next_tick = Instant::now() + delay;
+        if view.paused || state.done {
+            continue;
+        }
+
+        let index = policy.index(&state.board, state.board.agent_position);
+        let action = policy
+            .sample(index, &mut state.rng)
+            .ok_or_else(|| io::Error::other("Policy action out of bounds"))?;
+
+        state = state.step(action);
+        view.steps += 1;
+    }
+}
+
+fn draw(
+    out: &mut impl Write,
+    state: &GameState,
+    policy: &Policy,
+    config: &GGConfig,
+    view: &ViewState,
+) -> io::Result<()> {
+    let board = &state.board;
+
+    queue!(
+        out,
+        cursor::MoveTo(0, 0),
+        style::Print(format!(
+            "Generation Seed: {} | Episode Seed: {}\r\n",
+            config.generation_seed.unwrap_or_default(),
+            state.rng_seed,
+        ))
+    )?;
+
+    let width = cell_width(state);
+    for y in 0..board.height {
+        for x in 0..board.width {
+            let (text, foreground, background) = if board.ghost_position == Some((x, y)) {
+                ("G".to_string(), Color::Black, GHOST_COLOR)
+            } else if board.agent_position == (x, y) {
+                ("@".to_string(), Color::White, AGENT_COLOR)
+            } else {
+                match board.get(&(x, y)) {
+                    EntityType::Wall() => (String::new(), Color::White, WALL_COLOR),
+                    EntityType::Goblet(reward) => {
+                        let color = if reward > 0 {
+                            GOBLET_COLOR
+                        } else {
+                            FALSE_GOBLET_COLOR
+                        };
+                        (
+                            format!("{:>width$}", format!("{:+}", reward)),
+                            Color::Black,
+                            color,
+                        )
+                    }
+                    _ if view.show_policy => {
+                        let arrow = match policy.greedy(policy.index(board, (x, y))) {
+                            Some(Action::Up) => '↑',
+                            Some(Action::Down) => '↓',
+                            Some(Action::Left) => '←',
+                            Some(Action::Right) => '→',
+                            None => '?',
+                        };
+                        (arrow.to_string(), Color::Black, GROUND_COLOR)
+                    }
+                    _ => (String::new(), Color::Black, GROUND_COLOR),
+                }
+            };
+            let text = format!("{:^width$}", text);
+
+            queue!(
+                out,
+                style::PrintStyledContent(text.with(foreground).on(background))
+            )?;
+        }
+        queue!(out, style::Print("\r\n"))?;
+    }
+
+    let status = if state.done {
+        format!(
+            "Episode finished after {} steps with reward {}",
+            view.steps, state.reward
+        )
+    } else if view.paused {
+        format!("Step: {} | Paused", view.steps)
+    } else {
+        format!("Step: {} | Playing", view.steps)
+    };
+
+    queue!(
+        out,
+        style::Print(format!("{}\r\n", status)),
+        style::Print(
+            "Space: Play/Pause | Arrow Keys: Move Agent | P: Toggle Policy | R: Reset | Q: Quit\r\n"
+        ),
+        terminal::Clear(terminal::ClearType::FromCursorDown),
+    )?;
+
+    out.flush()
+}
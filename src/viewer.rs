@@ -0,0 +1,223 @@
+use std::sync::{
+    Mutex,
+    atomic::{AtomicBool, Ordering},
+    mpsc::{Receiver, Sender},
+};
+use std::thread::JoinHandle;
+
+use bevy::prelude::*;
+use pyo3::{exceptions::PyRuntimeError, prelude::*};
+use pyo3_stub_gen::derive::{gen_stub_pyclass, gen_stub_pymethods};
+
+use crate::{
+    agent::Playback,
+    core::{GGConfig, Policy, UpdateSets},
+    game_state::{Board, BoardEditedMessage, GameState},
+    policy::{self, PolicyLayout},
+};
+
+/// Whether a window has been opened. The windowing library refuses to create a second event
+/// loop in a process, even after the first one has stopped, so a second window would abort
+/// the app instead of opening.
+static WINDOW_OPENED: AtomicBool = AtomicBool::new(false);
+
+/// Claims the one window this process can open, or explains why it cannot be opened.
+pub fn claim_window() -> PyResult<()> {
+    if WINDOW_OPENED.swap(true, Ordering::SeqCst) {
+        return Err(PyRuntimeError::new_err(
+            "A window has already been opened in this process, and the windowing library cannot open another one after it closes; restart the Python process to open a new window",
+        ));
+    }
+    Ok(())
+}
+
+/// Sent from a `Viewer` handle to the app it drives.
+pub enum ViewerCommand {
+    Show(Box<GameState>),
+    SetPolicy(Policy),
+    Close,
+}
+
+#[derive(Resource)]
+struct ViewerCommands(Mutex<Receiver<ViewerCommand>>);
+
+/// Lets a `Viewer` handle drive the app from another thread.
+pub struct ViewerPlugin {
+    pub receiver: Mutex<Option<Receiver<ViewerCommand>>>,
+    /// Whether the app starts paused, e.g. because it was opened without a policy.
+    pub paused: bool,
+}
+
+impl Plugin for ViewerPlugin {
+    fn build(&self, app: &mut App) {
+        let receiver = self
+            .receiver
+            .lock()
+            .expect("Viewer receiver lock should not be poisoned")
+            .take()
+            .expect("ViewerPlugin should only be built once");
+
+        app.insert_resource(ViewerCommands(Mutex::new(receiver)));
+        app.insert_resource(Playback {
+            paused: self.paused,
+            ..default()
+        });
+        app.add_systems(Update, receive_commands.before(UpdateSets::EditBoard));
+    }
+}
+
+/// Applies the commands sent since the last frame. A shown state replaces the current one
+/// through the board editor's path and pauses playback so that it stays on screen; a new
+/// policy resumes it.
+fn receive_commands(
+    commands: Res<ViewerCommands>,
+    mut policy: ResMut<Policy>,
+    mut playback: ResMut<Playback>,
+    mut writer: MessageWriter<BoardEditedMessage>,
+    mut exit: MessageWriter<AppExit>,
+) {
+    let receiver = commands
+        .0
+        .lock()
+        .expect("Viewer receiver lock should not be poisoned");
+
+    for command in receiver.try_iter() {
+        match command {
+            ViewerCommand::Show(state) => {
+                playback.paused = true;
+                writer.write(BoardEditedMessage {
+                    board: state.board.clone(),
+                    state: Some(*state),
+                });
+            }
+            ViewerCommand::SetPolicy(new_policy) => {
+                *policy = new_policy;
+                playback.paused = false;
+            }
+            ViewerCommand::Close => {
+                exit.write(AppExit::Success);
+            }
+        }
+    }
+}
+
+/// A window that keeps running while Python does other work, returned by `open_viewer`.
+#[gen_stub_pyclass]
+#[pyclass(name = "Viewer")]
+pub struct Viewer {
+    sender: Sender<ViewerCommand>,
+    thread: Option<JoinHandle<()>>,
+    config: GGConfig,
+    board: Board,
+    policy: Policy,
+    layout: PolicyLayout,
+}
+
+impl Viewer {
+    /// Runs the app for `config` on a thread of its own, starting from `game_state`. Without a
+    /// `policy` the app starts paused with a uniformly random one.
+    pub fn open(
+        config: GGConfig,
+        game_state: GameState,
+        policy: Option<Policy>,
+        layout: PolicyLayout,
+    ) -> PyResult<Self> {
+        if cfg!(target_os = "macos") {
+            return Err(PyRuntimeError::new_err(
+                "open_viewer is not supported on macOS, where windows can only be opened from the main thread; use run instead",
+            ));
+        }
+        claim_window()?;
+
+        let board = game_state.board.clone();
+        let paused = policy.is_none();
+        let policy = policy.unwrap_or_else(|| Policy {
+            probabilities: vec![Some([0.25; 4]); board.width * board.height],
+            ghost_aware: false,
+        });
+
+        let (sender, receiver) = std::sync::mpsc::channel();
+        let plugin = ViewerPlugin {
+            receiver: Mutex::new(Some(receiver)),
+            paused,
+        };
+        let app_config = config.clone();
+        let app_policy = policy.clone();
+        let thread = std::thread::Builder::new()
+            .name("gg-viewer".to_string())
+            .spawn(move || {
+                crate::generate_app(app_config, game_state, app_policy, None, Some(plugin)).run();
+            })
+            .map_err(|e| PyRuntimeError::new_err(format!("Failed to start the viewer: {}", e)))?;
+
+        Ok(Self {
+            sender,
+            thread: Some(thread),
+            config,
+            board,
+            policy,
+            layout,
+        })
+    }
+
+    fn send(&self, command: ViewerCommand) -> PyResult<()> {
+        self.sender
+            .send(command)
+            .map_err(|_| PyRuntimeError::new_err("The viewer window has been closed"))
+    }
+}
+
+#[gen_stub_pymethods]
+#[pymethods]
+impl Viewer {
+    /// Whether the window is still open.
+    #[getter]
+    fn is_open(&self) -> bool {
+        self.thread
+            .as_ref()
+            .is_some_and(|thread| !thread.is_finished())
+    }
+
+    /// Shows `state`, e.g. one returned by `GameState.step`, and pauses playback on it. Press
+    /// Space in the window to let the policy play on from there. Raises `InvalidConfigError`
+    /// when the state's board does not fit the viewer's config, and `PolicyShapeError` when
+    /// the current policy has no action for one of its free cells.
+    fn show(&mut self, state: GameState) -> PyResult<()> {
+        state.board.check_shape(&self.config)?;
+        policy::check_coverage(&self.policy, &state.board)?;
+
+        self.board = state.board.clone();
+        self.send(ViewerCommand::Show(Box::new(state)))
+    }
+
+    /// Replaces the policy, read like the `policy` of `run` against the board shown last, and
+    /// resumes playback.
+    fn set_policy(&mut self, py: Python<'_>, policy: Py<PyAny>) -> PyResult<()> {
+        let policy = policy::extract_policy(py, &policy, &self.board, self.layout)?;
+        self.policy = policy.clone();
+        self.send(ViewerCommand::SetPolicy(policy))
+    }
+
+    /// Closes the window and waits for the app to stop.
+    fn close(&mut self, py: Python<'_>) -> PyResult<()> {
+        let Some(thread) = self.thread.take() else {
+            return Ok(());
+        };
+
+        // The app may already have stopped because its window was closed.
+        let _ = self.sender.send(ViewerCommand::Close);
+        py.detach(|| thread.join())
+            .map_err(|_| PyRuntimeError::new_err("The viewer stopped with an error"))
+    }
+}
+
+/// Closes the window when the handle is garbage collected, waiting for the app to stop like
+/// `close` does.
+impl Drop for Viewer {
+    fn drop(&mut self) {
+        let _ = self.sender.send(ViewerCommand::Close);
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}